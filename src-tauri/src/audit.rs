@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// One tool invocation, stored as a line of `tool-audit.jsonl` in the app data dir.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch, like the timestamps on chat messages.
+    pub timestamp: u64,
+    pub session_id: Option<String>,
+    /// MCP server id, or `None` for client-side tools.
+    pub server: Option<String>,
+    pub tool: String,
+    pub arguments: Value,
+    pub duration_ms: u64,
+    /// Size of the serialized result in bytes.
+    pub result_size: usize,
+    pub is_error: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFilter {
+    pub session_id: Option<String>,
+    pub server: Option<String>,
+    /// Inclusive bounds in milliseconds since the Unix epoch.
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    /// Keep only the most recent entries.
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.session_id
+            .as_ref()
+            .is_none_or(|id| entry.session_id.as_ref() == Some(id))
+            && self
+                .server
+                .as_ref()
+                .is_none_or(|server| entry.server.as_ref() == Some(server))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+/// Serializes appends so concurrent calls never interleave lines.
+#[derive(Default)]
+pub struct AuditState {
+    lock: Mutex<()>,
+}
+
+impl AuditState {
+    pub async fn record(&self, app: &AppHandle, entry: &AuditEntry) -> Result<(), String> {
+        let path = audit_path(app)?;
+        let mut line = serde_json::to_vec(entry).map_err(|e| e.to_string())?;
+        line.push(b'\n');
+
+        let _guard = self.lock.lock().await;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| e.to_string())?;
+        file.write_all(&line).await.map_err(|e| e.to_string())
+    }
+
+    pub async fn query(
+        &self,
+        app: &AppHandle,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEntry>, String> {
+        let path = audit_path(app)?;
+        let data = {
+            let _guard = self.lock.lock().await;
+            match tokio::fs::read(path).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+
+        let mut entries: Vec<AuditEntry> =
+            parse(&data).filter(|entry| filter.matches(entry)).collect();
+        if let Some(limit) = filter.limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        Ok(entries)
+    }
+}
+
+/// Reads the log's entries. A line cut short by a crash, even mid-character, is skipped
+/// rather than hiding the rest of the log.
+fn parse(data: &[u8]) -> impl Iterator<Item = AuditEntry> + '_ {
+    data.split(|&byte| byte == b'\n')
+        .filter_map(|line| serde_json::from_slice(line).ok())
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn audit_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("tool-audit.jsonl"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_broken_line_does_not_hide_later_entries() {
+        let entry = |tool: &str| AuditEntry {
+            timestamp: 1,
+            session_id: None,
+            server: None,
+            tool: tool.to_string(),
+            arguments: Value::Null,
+            duration_ms: 0,
+            result_size: 0,
+            is_error: false,
+            error: None,
+        };
+        let mut data = serde_json::to_vec(&entry("first")).unwrap();
+        // A write cut off in the middle of a multi-byte character.
+        data.extend_from_slice(b"\n{\"tool\":\"\xE2\x82\n");
+        data.extend(serde_json::to_vec(&entry("second")).unwrap());
+
+        let tools: Vec<String> = parse(&data).map(|entry| entry.tool).collect();
+        assert_eq!(tools, ["first", "second"]);
+    }
+}
@@ -1,21 +1,32 @@
+mod audit;
 mod mcp;
 
-use rmcp::model::CallToolRequestParam;
+use audit::{AuditEntry, AuditFilter, AuditState};
+
+use mcp::calls::{CallContext, CallsState};
+use mcp::catalog::{CatalogEntry, CatalogState};
+use mcp::elicitation::ElicitationState;
+use mcp::output::ToolOutput;
+use mcp::policy::PolicyState;
+use mcp::roots::RootsState;
+use mcp::sampling::SamplingState;
+use mcp::McpState;
+use rmcp::model::{
+    ArgumentInfo, CompleteRequestParam, CompletionContext, CompletionInfo, ElicitationAction,
+    GetPromptRequestParam, ReadResourceRequestParam, Reference, SubscribeRequestParam,
+    UnsubscribeRequestParam,
+};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Arc;
+use tauri::ipc::Channel;
+use tauri::AppHandle;
 use tauri::Manager;
 use tauri::State;
-use tokio::sync::Mutex;
 #[cfg(target_os = "windows")]
 use window_vibrancy::apply_mica;
 #[cfg(target_os = "macos")]
 use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
 
-struct McpState {
-    clients: Mutex<HashMap<String, Arc<mcp::McpClient>>>,
-}
-
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
@@ -23,63 +34,464 @@ fn greet(name: &str) -> String {
 
 #[tauri::command]
 async fn mcp_connect(
+    app: AppHandle,
     state: State<'_, McpState>,
     id: String,
-    url: String,
-    transport: Option<String>,
-) -> Result<(), String> {
-    // Use provided transport or heuristic
-    let transport_type = match transport.as_deref() {
-        Some("sse") => mcp::TransportType::Sse,
-        Some("http") => mcp::TransportType::StreamableHttp,
-        _ => {
-            if url.contains("/sse") {
-                mcp::TransportType::Sse
-            } else {
-                mcp::TransportType::StreamableHttp
-            }
-        }
+    config: mcp::ServerConfig,
+) -> Result<mcp::TransportType, String> {
+    let (client, transport) = mcp::connect_or_authorize(&app, &id, &config).await?;
+    state.register(&app, id, config, client, transport).await;
+    Ok(transport)
+}
+
+/// Imports servers from an `mcpServers` config, given either as pasted JSON or as a file path.
+#[tauri::command]
+async fn mcp_import_servers(
+    app: AppHandle,
+    state: State<'_, McpState>,
+    json: Option<String>,
+    path: Option<String>,
+    existing: Option<Vec<String>>,
+) -> Result<Vec<mcp::import::ImportReport>, String> {
+    let json = match (json, path) {
+        (Some(json), _) => json,
+        (None, Some(path)) => tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("Could not read {path}: {e}"))?,
+        (None, None) => return Err("Nothing to import".to_string()),
     };
+    mcp::import::import(&app, &state, &json, &existing.unwrap_or_default()).await
+}
+
+/// Stores a secret in the system keychain for use in a server's `secretEnv`, `secretHeaders`
+/// or bearer token.
+#[tauri::command]
+async fn mcp_secret_set(name: String, value: String) -> Result<(), String> {
+    keychain(move || mcp::secrets::set(&name, &value)).await?
+}
+
+#[tauri::command]
+async fn mcp_secret_delete(name: String) -> Result<(), String> {
+    keychain(move || mcp::secrets::delete(&name)).await?
+}
 
-    let client = mcp::connect(&url, transport_type)
+/// Whether each named secret is stored, without revealing its value.
+#[tauri::command]
+async fn mcp_secret_status(names: Vec<String>) -> Result<HashMap<String, bool>, String> {
+    keychain(move || {
+        names
+            .into_iter()
+            .map(|name| {
+                let exists = mcp::secrets::exists(&name);
+                (name, exists)
+            })
+            .collect()
+    })
+    .await
+}
+
+/// Runs keychain access on the blocking pool, as the platform stores can wait on an unlock
+/// prompt.
+async fn keychain<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(f)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mcp_disconnect(
+    app: AppHandle,
+    state: State<'_, McpState>,
+    id: String,
+) -> Result<(), String> {
+    state.remove(&app, &id).await;
+    Ok(())
+}
+
+/// Closes the session of a server that is being deleted and removes its stored sign-in.
+#[tauri::command]
+async fn mcp_forget_server(
+    app: AppHandle,
+    state: State<'_, McpState>,
+    id: String,
+) -> Result<(), String> {
+    state.remove(&app, &id).await;
+    mcp::oauth::forget(&id).await
+}
 
-    state.clients.lock().await.insert(id, Arc::new(client));
+#[tauri::command]
+async fn mcp_disconnect_all(app: AppHandle, state: State<'_, McpState>) -> Result<(), String> {
+    state.remove_all(&app).await;
     Ok(())
 }
 
 #[tauri::command]
-async fn mcp_list_tools(state: State<'_, McpState>, id: String) -> Result<Value, String> {
-    let clients = state.clients.lock().await;
-    let client = clients.get(&id).ok_or("Client not found")?;
+async fn mcp_reconnect(
+    app: AppHandle,
+    state: State<'_, McpState>,
+    id: String,
+) -> Result<(), String> {
+    let config = {
+        let clients = state.clients.lock().await;
+        let connection = clients.get(&id).ok_or("Client not found")?;
+        connection.config.clone()
+    };
+    mcp_connect(app, state, id, config).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn mcp_status(state: State<'_, McpState>) -> Result<Vec<mcp::McpStatus>, String> {
+    Ok(state.statuses().await)
+}
+
+/// What the server declared during initialization: protocol version, implementation name and
+/// version, capabilities and instructions.
+#[tauri::command]
+async fn mcp_server_info(state: State<'_, McpState>, id: String) -> Result<Value, String> {
+    let client = state.client(&id).await?;
+    let info = client
+        .peer_info()
+        .ok_or_else(|| format!("Server {id} has not finished initializing"))?;
+    serde_json::to_value(info).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mcp_list_tools(
+    state: State<'_, McpState>,
+    catalog: State<'_, CatalogState>,
+    id: String,
+) -> Result<Value, String> {
+    let tools = catalog.tools(&state, &id).await?;
+    Ok(serde_json::json!({ "tools": tools }))
+}
+
+#[tauri::command]
+async fn mcp_catalog(
+    state: State<'_, McpState>,
+    catalog: State<'_, CatalogState>,
+    ids: Option<Vec<String>>,
+) -> Result<Vec<CatalogEntry>, String> {
+    Ok(catalog.entries(&state, ids).await)
+}
+
+#[tauri::command]
+async fn mcp_call_tool(
+    app: AppHandle,
+    id: String,
+    name: String,
+    args: Value,
+    call_id: Option<String>,
+    on_progress: Option<Channel<mcp::calls::ProgressUpdate>>,
+    context: Option<CallContext>,
+) -> Result<ToolOutput, String> {
+    let context = context.unwrap_or_default();
+    let started = std::time::Instant::now();
+    let timestamp = audit::now_ms();
+    let result = mcp::calls::run(
+        &app,
+        &id,
+        name.clone(),
+        args.clone(),
+        call_id,
+        on_progress,
+        &context,
+    )
+    .await;
+
+    let entry = AuditEntry {
+        timestamp,
+        session_id: context.session_id,
+        server: Some(id),
+        tool: name,
+        arguments: args,
+        duration_ms: started.elapsed().as_millis() as u64,
+        result_size: result.as_ref().map_or(0, |output| {
+            serde_json::to_vec(output).map_or(0, |data| data.len())
+        }),
+        is_error: result.as_ref().map_or(true, |output| output.is_error),
+        error: result.as_ref().err().cloned(),
+    };
+    // Losing an audit line shouldn't fail the call it describes.
+    let _ = app.state::<AuditState>().record(&app, &entry).await;
+
+    result
+}
+
+/// Calls a tool by the qualified name it was given in the catalog.
+#[tauri::command]
+async fn mcp_call_qualified(
+    app: AppHandle,
+    catalog: State<'_, CatalogState>,
+    qualified_name: String,
+    args: Value,
+    call_id: Option<String>,
+    on_progress: Option<Channel<mcp::calls::ProgressUpdate>>,
+    context: Option<CallContext>,
+) -> Result<ToolOutput, String> {
+    let (id, name) = catalog
+        .route(&qualified_name)
+        .ok_or_else(|| format!("Unknown tool: {qualified_name}"))?;
+    mcp_call_tool(app, id, name, args, call_id, on_progress, context).await
+}
+
+#[tauri::command]
+fn mcp_cancel_call(
+    calls: State<'_, CallsState>,
+    session_id: Option<String>,
+    call_id: String,
+) -> bool {
+    calls.cancel(&(session_id, call_id))
+}
+
+#[tauri::command]
+async fn mcp_list_resources(state: State<'_, McpState>, id: String) -> Result<Value, String> {
+    let client = state.client(&id).await?;
 
     let result = client
-        .list_tools(Default::default())
+        .list_all_resources()
         .await
         .map_err(|e| e.to_string())?;
     serde_json::to_value(result).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn mcp_call_tool(
+async fn mcp_list_resource_templates(
     state: State<'_, McpState>,
     id: String,
-    name: String,
-    args: Value,
 ) -> Result<Value, String> {
-    let clients = state.clients.lock().await;
-    let client = clients.get(&id).ok_or("Client not found")?;
+    let client = state.client(&id).await?;
 
-    let param = CallToolRequestParam {
-        name: name.into(),
-        arguments: args.as_object().cloned(),
-    };
+    let result = client
+        .list_all_resource_templates()
+        .await
+        .map_err(|e| e.to_string())?;
+    serde_json::to_value(result).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mcp_read_resource(
+    state: State<'_, McpState>,
+    id: String,
+    uri: String,
+) -> Result<Value, String> {
+    let client = state.client(&id).await?;
+
+    let result = client
+        .read_resource(ReadResourceRequestParam { uri })
+        .await
+        .map_err(|e| e.to_string())?;
+    serde_json::to_value(result).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mcp_subscribe_resource(
+    state: State<'_, McpState>,
+    id: String,
+    uri: String,
+) -> Result<(), String> {
+    let client = state.client(&id).await?;
+
+    client
+        .subscribe(SubscribeRequestParam { uri })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mcp_unsubscribe_resource(
+    state: State<'_, McpState>,
+    id: String,
+    uri: String,
+) -> Result<(), String> {
+    let client = state.client(&id).await?;
+
+    client
+        .unsubscribe(UnsubscribeRequestParam { uri })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mcp_list_prompts(state: State<'_, McpState>, id: String) -> Result<Value, String> {
+    let client = state.client(&id).await?;
 
-    let result = client.call_tool(param).await.map_err(|e| e.to_string())?;
+    let result = client.list_all_prompts().await.map_err(|e| e.to_string())?;
     serde_json::to_value(result).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn mcp_get_prompt(
+    state: State<'_, McpState>,
+    id: String,
+    name: String,
+    arguments: Option<serde_json::Map<String, Value>>,
+) -> Result<mcp::prompts::RenderedPrompt, String> {
+    let client = state.client(&id).await?;
+
+    let result = client
+        .get_prompt(GetPromptRequestParam { name, arguments })
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.into())
+}
+
+/// Suggests values for a prompt or resource-template argument from what the user has typed.
+/// `arguments` holds the values already chosen for the other arguments.
+#[tauri::command]
+async fn mcp_complete(
+    state: State<'_, McpState>,
+    id: String,
+    reference: Reference,
+    argument: ArgumentInfo,
+    arguments: Option<HashMap<String, String>>,
+) -> Result<CompletionInfo, String> {
+    let client = state.client(&id).await?;
+
+    // Servers without the capability would only answer with "method not found".
+    let supported = client
+        .peer_info()
+        .is_some_and(|info| info.capabilities.completions.is_some());
+    if !supported {
+        return Ok(CompletionInfo::default());
+    }
+
+    let result = client
+        .complete(CompleteRequestParam {
+            r#ref: reference,
+            argument,
+            context: arguments.map(|arguments| CompletionContext {
+                arguments: Some(arguments),
+            }),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.completion)
+}
+
+#[tauri::command]
+fn mcp_set_sampling_config(
+    state: State<'_, SamplingState>,
+    config: Option<mcp::sampling::SamplingConfig>,
+) {
+    *state.config.write().unwrap() = config;
+}
+
+#[tauri::command]
+fn mcp_sampling_respond(
+    state: State<'_, SamplingState>,
+    request_id: String,
+    approved: bool,
+) -> Result<(), String> {
+    if state.approvals.resolve(&request_id, approved) {
+        Ok(())
+    } else {
+        Err("Sampling request not found".to_string())
+    }
+}
+
+/// Records a tool that ran in the webview rather than on an MCP server.
+#[tauri::command]
+async fn tool_audit_record(
+    app: AppHandle,
+    audit: State<'_, AuditState>,
+    entry: AuditEntry,
+) -> Result<(), String> {
+    audit.record(&app, &entry).await
+}
+
+#[tauri::command]
+async fn tool_audit_query(
+    app: AppHandle,
+    audit: State<'_, AuditState>,
+    filter: Option<AuditFilter>,
+) -> Result<Vec<AuditEntry>, String> {
+    audit.query(&app, &filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+fn tool_policy_get(
+    app: AppHandle,
+    policies: State<'_, PolicyState>,
+    project_id: Option<String>,
+) -> Result<Vec<mcp::policy::PolicyRule>, String> {
+    policies.get(&app, project_id.as_deref())
+}
+
+#[tauri::command]
+fn tool_policy_set(
+    app: AppHandle,
+    policies: State<'_, PolicyState>,
+    project_id: Option<String>,
+    rules: Vec<mcp::policy::PolicyRule>,
+) -> Result<(), String> {
+    policies.set(&app, project_id.as_deref(), rules)
+}
+
+#[tauri::command]
+fn tool_approval_respond(
+    policies: State<'_, PolicyState>,
+    request_id: String,
+    approved: bool,
+) -> Result<(), String> {
+    if policies.approvals.resolve(&request_id, approved) {
+        Ok(())
+    } else {
+        Err("Tool approval request not found".to_string())
+    }
+}
+
+#[tauri::command]
+async fn mcp_set_roots(
+    state: State<'_, McpState>,
+    roots: State<'_, RootsState>,
+    id: String,
+    directories: Vec<mcp::roots::RootDirectory>,
+) -> Result<(), String> {
+    if !roots.set(&id, directories)? {
+        return Ok(());
+    }
+
+    if let Ok(client) = state.client(&id).await {
+        client
+            .notify_roots_list_changed()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn mcp_get_roots(roots: State<'_, RootsState>, id: String) -> Vec<rmcp::model::Root> {
+    roots.get(&id)
+}
+
+#[tauri::command]
+fn mcp_elicitation_respond(
+    state: State<'_, ElicitationState>,
+    request_id: String,
+    reply: mcp::elicitation::ElicitationReply,
+) -> Result<(), String> {
+    let mut schemas = state.schemas.lock().unwrap();
+    let schema = schemas
+        .get(&request_id)
+        .ok_or("Elicitation request not found")?;
+    if reply.action == ElicitationAction::Accept {
+        let content = reply
+            .content
+            .as_ref()
+            .ok_or("Accepted reply has no content")?;
+        mcp::elicitation::validate_content(schema, content).map_err(|errors| errors.join("; "))?;
+    }
+
+    schemas.remove(&request_id);
+    if state.replies.resolve(&request_id, reply) {
+        Ok(())
+    } else {
+        Err("Elicitation request not found".to_string())
+    }
+}
+
 #[tauri::command]
 fn compress_data(data: Vec<u8>) -> Result<Vec<u8>, String> {
     let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
@@ -101,9 +513,14 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_http::init())
-        .manage(McpState {
-            clients: Mutex::new(HashMap::new()),
-        })
+        .manage(McpState::default())
+        .manage(SamplingState::default())
+        .manage(RootsState::default())
+        .manage(ElicitationState::default())
+        .manage(CallsState::default())
+        .manage(CatalogState::default())
+        .manage(PolicyState::default())
+        .manage(AuditState::default())
         .setup(|app| {
             let window = app.get_webview_window("main").unwrap();
 
@@ -120,8 +537,39 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             greet,
             mcp_connect,
+            mcp_import_servers,
+            mcp_secret_set,
+            mcp_secret_delete,
+            mcp_secret_status,
+            mcp_disconnect,
+            mcp_forget_server,
+            mcp_disconnect_all,
+            mcp_reconnect,
+            mcp_status,
+            mcp_server_info,
             mcp_list_tools,
+            mcp_catalog,
             mcp_call_tool,
+            mcp_call_qualified,
+            mcp_cancel_call,
+            mcp_list_resources,
+            mcp_list_resource_templates,
+            mcp_read_resource,
+            mcp_subscribe_resource,
+            mcp_unsubscribe_resource,
+            mcp_list_prompts,
+            mcp_get_prompt,
+            mcp_complete,
+            mcp_set_sampling_config,
+            mcp_sampling_respond,
+            tool_policy_get,
+            tool_policy_set,
+            tool_approval_respond,
+            tool_audit_record,
+            tool_audit_query,
+            mcp_set_roots,
+            mcp_get_roots,
+            mcp_elicitation_respond,
             compress_data,
             decompress_data
         ])
@@ -1,29 +1,605 @@
-use rmcp::service::{RoleClient, RunningService};
-use rmcp::transport::sse_client::SseClientTransport;
-use rmcp::transport::streamable_http_client::StreamableHttpClientTransport;
+pub mod approval;
+pub mod calls;
+pub mod catalog;
+pub mod elicitation;
+pub mod handler;
+pub mod import;
+pub mod oauth;
+pub mod output;
+pub mod policy;
+pub mod prompts;
+pub mod roots;
+pub mod sampling;
+pub mod schema;
+pub mod secrets;
+pub mod supervisor;
+pub mod websocket;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::StatusCode;
+use rmcp::service::{ClientInitializeError, RoleClient, RunningService};
+use rmcp::transport::auth::AuthClient;
+use rmcp::transport::sse_client::{SseClient, SseClientConfig, SseClientTransport};
+use rmcp::transport::streamable_http_client::{
+    StreamableHttpClient, StreamableHttpClientTransport, StreamableHttpClientTransportConfig,
+    StreamableHttpError,
+};
+use rmcp::transport::{ConfigureCommandExt, TokioChildProcess};
 use rmcp::ServiceExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+
+use catalog::CatalogState;
+use handler::McpHandler;
+use supervisor::Status;
+use websocket::WebSocketTransport;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TransportType {
+    /// Streamable HTTP, falling back to legacy SSE for servers that don't speak it.
+    Auto,
     Sse,
+    #[serde(rename = "http")]
     StreamableHttp,
+    Stdio,
+    #[serde(rename = "websocket")]
+    WebSocket,
+}
+
+/// Connection settings for a single MCP server, as stored in the frontend settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfig {
+    /// Display name, used to qualify the server's tool names.
+    pub name: Option<String>,
+    pub transport: Option<TransportType>,
+    /// Endpoint for the HTTP based and WebSocket transports.
+    pub url: Option<String>,
+    /// Executable to launch for the stdio transport.
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Variables whose values live in the system keychain, mapped to the secret's name. They
+    /// are only read when the process is spawned.
+    #[serde(default)]
+    pub secret_env: HashMap<String, String>,
+    /// Variables passed through from our own environment on top of [`INHERITED_ENV`].
+    #[serde(default)]
+    pub inherit_env: Vec<String>,
+    /// Extra headers sent with every request on the HTTP based transports and with the
+    /// WebSocket handshake.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Headers whose values live in the system keychain, mapped to the secret's name.
+    #[serde(default)]
+    pub secret_headers: HashMap<String, String>,
+    /// Name of the keychain secret holding the bearer token.
+    pub bearer_token_secret: Option<String>,
+}
+
+impl ServerConfig {
+    /// Resolves the transport to use, negotiating over HTTP when none was chosen.
+    pub fn transport_type(&self) -> TransportType {
+        match (self.transport, &self.url, &self.command) {
+            (Some(transport), _, _) => transport,
+            (None, None, Some(_)) => TransportType::Stdio,
+            _ => TransportType::Auto,
+        }
+    }
+
+    pub(crate) fn url(&self) -> Result<&str, String> {
+        match self.url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => Ok(url),
+            _ => Err("MCP server URL is required for this transport".to_string()),
+        }
+    }
+}
+
+pub type McpClient = RunningService<RoleClient, McpHandler>;
+
+/// A registered session. Generic over the client only so tests can use plain rmcp clients.
+pub struct McpConnection<C = McpClient> {
+    pub config: ServerConfig,
+    pub client: Arc<C>,
+    /// The transport actually in use, which differs from the config in auto mode.
+    pub transport: TransportType,
+    pub status: Status,
+    supervisor: AbortHandle,
+}
+
+impl McpConnection {
+    async fn close(self) {
+        self.supervisor.abort();
+        shutdown(self.client).await;
+    }
+}
+
+pub struct McpState<C = McpClient> {
+    pub clients: Mutex<HashMap<String, McpConnection<C>>>,
+}
+
+impl Default for McpState {
+    fn default() -> Self {
+        Self {
+            clients: Mutex::default(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct McpStatus {
+    pub id: String,
+    pub status: Status,
+    pub transport: TransportType,
+    pub connected: bool,
 }
 
-pub type McpClient = RunningService<RoleClient, ()>;
+impl<C> McpState<C> {
+    /// Returns a handle to the client for `id` without keeping the map locked, so calls
+    /// to different servers (and new connections) don't wait on each other.
+    pub async fn client(&self, id: &str) -> Result<Arc<C>, String> {
+        let clients = self.clients.lock().await;
+        clients
+            .get(id)
+            .map(|connection| connection.client.clone())
+            .ok_or_else(|| "Client not found".to_string())
+    }
+}
 
+impl McpState {
+    /// Stores a freshly connected client and starts its supervisor, closing any previous
+    /// session registered under the same id.
+    pub async fn register(
+        &self,
+        app: &AppHandle,
+        id: String,
+        config: ServerConfig,
+        client: McpClient,
+        transport: TransportType,
+    ) {
+        let connection = McpConnection {
+            config,
+            client: Arc::new(client),
+            transport,
+            status: Status::Connected,
+            supervisor: supervisor::spawn(app.clone(), id.clone()),
+        };
+        let previous = self.clients.lock().await.insert(id.clone(), connection);
+        app.state::<CatalogState>().invalidate(&id);
+        if let Some(previous) = previous {
+            previous.close().await;
+        }
+        supervisor::emit(app, &id, Status::Connected, 0, None);
+    }
+
+    /// Closes the session for `id`, returning whether one was open.
+    pub async fn remove(&self, app: &AppHandle, id: &str) -> bool {
+        let removed = self.clients.lock().await.remove(id);
+        match removed {
+            Some(connection) => {
+                app.state::<CatalogState>().invalidate(id);
+                connection.close().await;
+                supervisor::emit(app, id, Status::Disconnected, 0, None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn remove_all(&self, app: &AppHandle) {
+        let removed: Vec<_> = self.clients.lock().await.drain().collect();
+        for (id, connection) in removed {
+            app.state::<CatalogState>().invalidate(&id);
+            connection.close().await;
+            supervisor::emit(app, &id, Status::Disconnected, 0, None);
+        }
+    }
+
+    pub async fn statuses(&self) -> Vec<McpStatus> {
+        let clients = self.clients.lock().await;
+        clients
+            .iter()
+            .map(|(id, connection)| McpStatus {
+                id: id.clone(),
+                status: connection.status,
+                transport: connection.transport,
+                connected: connection.status == Status::Connected
+                    && !connection.client.is_transport_closed(),
+            })
+            .collect()
+    }
+}
+
+/// Stops a client's service loop, which closes its transport and any child process.
+///
+/// If the client is still shared with an in-flight call we can only signal cancellation,
+/// in which case that call fails with a closed transport.
+pub async fn shutdown(client: Arc<McpClient>) {
+    match Arc::try_unwrap(client) {
+        Ok(client) => {
+            let _ = client.cancel().await;
+        }
+        Err(client) => client.cancellation_token().cancel(),
+    }
+}
+
+/// Opens a session with a server, returning it along with the transport that was used.
 pub async fn connect(
+    app: &AppHandle,
+    id: &str,
+    config: &ServerConfig,
+) -> Result<(McpClient, TransportType), Box<dyn std::error::Error + Send + Sync>> {
+    let handler = || McpHandler::new(app.clone(), id.to_string());
+    let transport = config.transport_type();
+    match transport {
+        TransportType::Stdio => {
+            let t = TokioChildProcess::new(stdio_command(config).await?)?;
+            let service = handler().serve(t).await?;
+            Ok((service, transport))
+        }
+        TransportType::WebSocket => {
+            let t = WebSocketTransport::connect(config.url()?, headers(config).await?).await?;
+            let service = handler().serve(t).await?;
+            Ok((service, transport))
+        }
+        _ => {
+            let url = config.url()?;
+            let client = http_client(config).await?;
+            match oauth::stored_authorization(id, url).await? {
+                Some(manager) => {
+                    let client = AuthClient::new(client, manager);
+                    negotiate(transport, url, client, handler).await
+                }
+                None => negotiate(transport, url, client, handler).await,
+            }
+        }
+    }
+}
+
+/// Like [`connect`], but when an HTTP server turns us away because it uses OAuth, runs the
+/// sign-in flow and tries once more.
+pub async fn connect_or_authorize(
+    app: &AppHandle,
+    id: &str,
+    config: &ServerConfig,
+) -> Result<(McpClient, TransportType), String> {
+    match connect(app, id, config).await {
+        Ok(connected) => Ok(connected),
+        Err(e)
+            if !matches!(
+                config.transport_type(),
+                TransportType::Stdio | TransportType::WebSocket
+            ) =>
+        {
+            if !oauth::requires_authorization(config).await {
+                return Err(e.to_string());
+            }
+            oauth::authorize(app, id, config.url.as_deref().unwrap_or_default()).await?;
+            connect(app, id, config).await.map_err(|e| e.to_string())
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Connects over HTTP. In auto mode streamable HTTP is tried first and, following the spec's
+/// backwards compatibility guidance, a server that rejects it is retried as a legacy SSE server.
+async fn negotiate<C>(
+    transport: TransportType,
     url: &str,
-    transport_type: TransportType,
-) -> Result<McpClient, Box<dyn std::error::Error>> {
-    match transport_type {
-        TransportType::Sse => {
-            let t = SseClientTransport::start(url.to_owned()).await?;
-            let service = ().serve(t).await?;
-            Ok(service)
-        }
-        TransportType::StreamableHttp => {
-            let t = StreamableHttpClientTransport::from_uri(url);
-            let service = ().serve(t).await?;
-            Ok(service)
+    client: C,
+    handler: impl Fn() -> McpHandler,
+) -> Result<(McpClient, TransportType), Box<dyn std::error::Error + Send + Sync>>
+where
+    C: SseClient + StreamableHttpClient + Clone,
+{
+    if transport != TransportType::Auto {
+        let service = connect_http(transport, url, client, handler()).await?;
+        return Ok((service, transport));
+    }
+
+    let streamable = TransportType::StreamableHttp;
+    match connect_http(streamable, url, client.clone(), handler()).await {
+        Ok(service) => Ok((service, streamable)),
+        Err(http_error) if !rejected_initialize(http_error.as_ref()) => Err(http_error),
+        Err(http_error) => match connect_http(TransportType::Sse, url, client, handler()).await {
+            Ok(service) => Ok((service, TransportType::Sse)),
+            Err(sse_error) => Err(format!(
+                "Streamable HTTP failed ({http_error}) and SSE fallback failed ({sse_error})"
+            )
+            .into()),
+        },
+    }
+}
+
+/// Whether a failed Streamable HTTP handshake points at a legacy SSE server: the initialize
+/// POST itself was turned away with 400, 404 or 405. Anything else, like an unreachable host,
+/// a timeout or a 401, would fail the same way over SSE.
+fn rejected_initialize(error: &(dyn std::error::Error + 'static)) -> bool {
+    let Some(ClientInitializeError::TransportError { error, .. }) = error.downcast_ref() else {
+        return false;
+    };
+    match error
+        .error
+        .downcast_ref::<StreamableHttpError<reqwest::Error>>()
+    {
+        Some(StreamableHttpError::Client(e)) => matches!(
+            e.status(),
+            Some(StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED)
+        ),
+        _ => false,
+    }
+}
+
+async fn connect_http<C>(
+    transport: TransportType,
+    url: &str,
+    client: C,
+    handler: McpHandler,
+) -> Result<McpClient, Box<dyn std::error::Error + Send + Sync>>
+where
+    C: SseClient + StreamableHttpClient,
+{
+    if transport == TransportType::Sse {
+        let sse_config = SseClientConfig {
+            sse_endpoint: url.into(),
+            ..Default::default()
+        };
+        let t = SseClientTransport::start_with_client(client, sse_config).await?;
+        let service = handler.serve(t).await?;
+        Ok(service)
+    } else {
+        let t = StreamableHttpClientTransport::with_client(
+            client,
+            StreamableHttpClientTransportConfig::with_uri(url),
+        );
+        let service = handler.serve(t).await?;
+        Ok(service)
+    }
+}
+
+/// Collects a server's configured headers and bearer token, reading the secret ones from the
+/// keychain.
+async fn headers(config: &ServerConfig) -> Result<HeaderMap, String> {
+    let mut values: Vec<(String, String)> = config.headers.clone().into_iter().collect();
+    let names = config.secret_headers.values().cloned().collect();
+    let secret_values = secrets::get_all(names).await?;
+    values.extend(config.secret_headers.keys().cloned().zip(secret_values));
+    if let Some(secret) = &config.bearer_token_secret {
+        let token = secrets::get_all(vec![secret.clone()]).await?.remove(0);
+        values.push((
+            AUTHORIZATION.to_string(),
+            format!("Bearer {}", token.trim()),
+        ));
+    }
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &values {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|e| format!("Invalid header name `{name}`: {e}"))?;
+        let mut value = HeaderValue::from_str(value.trim())
+            .map_err(|e| format!("Invalid value for header `{name}`: {e}"))?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Builds the HTTP client for a server, with its headers and bearer token set as defaults so
+/// they go out on every request, including SSE reconnects.
+pub(crate) async fn http_client(config: &ServerConfig) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .default_headers(headers(config).await?)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// What a stdio server gets from our environment by default: enough to find executables and
+/// behave like a normal user process, and nothing like API keys the app happens to have.
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TZ",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "TERM",
+    "XDG_RUNTIME_DIR",
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "USERPROFILE",
+    "USERNAME",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+    "PROGRAMFILES(X86)",
+];
+
+/// The complete environment for a stdio server: the inherited allowlist, then the configured
+/// values, then secrets from the keychain.
+async fn stdio_env(config: &ServerConfig) -> Result<HashMap<String, String>, String> {
+    let inherited = INHERITED_ENV
+        .iter()
+        .copied()
+        .chain(config.inherit_env.iter().map(String::as_str));
+    let mut env: HashMap<String, String> = inherited
+        .filter_map(|key| Some((key.to_string(), std::env::var(key).ok()?)))
+        .collect();
+    env.extend(config.env.clone());
+    let names = config.secret_env.values().cloned().collect();
+    let values = secrets::get_all(names).await?;
+    env.extend(config.secret_env.keys().cloned().zip(values));
+    Ok(env)
+}
+
+async fn stdio_command(config: &ServerConfig) -> Result<Command, String> {
+    let program = match config.command.as_deref().map(str::trim) {
+        Some(program) if !program.is_empty() => program,
+        _ => return Err("MCP server command is required for the stdio transport".to_string()),
+    };
+
+    let env = stdio_env(config).await?;
+    Ok(Command::new(program).configure(|cmd| {
+        cmd.args(&config.args).env_clear().envs(env);
+        if let Some(cwd) = config.cwd.as_deref().filter(|cwd| !cwd.is_empty()) {
+            cmd.current_dir(cwd);
         }
+        // Don't flash a console window for every server we launch on Windows.
+        #[cfg(target_os = "windows")]
+        cmd.creation_flags(0x08000000);
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{CallToolRequestParam, CallToolResult, Content};
+    use rmcp::service::{RequestContext, RoleServer};
+    use rmcp::{ErrorData, ServerHandler};
+    use std::time::{Duration, Instant};
+
+    /// Starts a Streamable HTTP handshake against a local server that answers every request
+    /// with `status`, returning the error it fails with.
+    async fn initialize_error(status: &'static str) -> Box<dyn std::error::Error + Send + Sync> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = stream.read(&mut [0u8; 8192]).await;
+                let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n");
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let transport = StreamableHttpClientTransport::with_client(
+            reqwest::Client::new(),
+            StreamableHttpClientTransportConfig::with_uri(url),
+        );
+        ().serve(transport).await.map(|_| ()).unwrap_err().into()
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_sse_only_when_initialize_is_rejected() {
+        for status in ["400 Bad Request", "404 Not Found", "405 Method Not Allowed"] {
+            assert!(
+                rejected_initialize(initialize_error(status).await.as_ref()),
+                "{status}"
+            );
+        }
+        for status in ["401 Unauthorized", "500 Internal Server Error"] {
+            assert!(
+                !rejected_initialize(initialize_error(status).await.as_ref()),
+                "{status}"
+            );
+        }
+
+        // Nothing listening on the port
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        drop(listener);
+        let transport = StreamableHttpClientTransport::with_client(
+            reqwest::Client::new(),
+            StreamableHttpClientTransportConfig::with_uri(url),
+        );
+        let error: Box<dyn std::error::Error + Send + Sync> =
+            ().serve(transport).await.map(|_| ()).unwrap_err().into();
+        assert!(!rejected_initialize(error.as_ref()));
+    }
+
+    type Spans = Arc<std::sync::Mutex<Vec<(Instant, Instant)>>>;
+
+    /// A server whose only tool takes a while, recording when each call ran.
+    #[derive(Clone)]
+    struct SlowServer {
+        spans: Spans,
+    }
+
+    impl ServerHandler for SlowServer {
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParam,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, ErrorData> {
+            let started = Instant::now();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            self.spans.lock().unwrap().push((started, Instant::now()));
+            Ok(CallToolResult::success(vec![Content::text("done")]))
+        }
+    }
+
+    async fn register(state: &McpState<RunningService<RoleClient, ()>>, id: &str, spans: &Spans) {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let server = SlowServer {
+            spans: spans.clone(),
+        };
+        tokio::spawn(async move {
+            if let Ok(server) = server.serve(server_io).await {
+                let _ = server.waiting().await;
+            }
+        });
+        let client = ().serve(client_io).await.unwrap();
+
+        let connection = McpConnection {
+            config: ServerConfig::default(),
+            client: Arc::new(client),
+            transport: TransportType::Stdio,
+            status: Status::Connected,
+            supervisor: tokio::spawn(async {}).abort_handle(),
+        };
+        state
+            .clients
+            .lock()
+            .await
+            .insert(id.to_string(), connection);
+    }
+
+    async fn call(state: &McpState<RunningService<RoleClient, ()>>, id: &str) {
+        let client = state.client(id).await.unwrap();
+        let param = CallToolRequestParam {
+            name: "slow".into(),
+            arguments: None,
+        };
+        client.call_tool(param).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn calls_to_different_servers_overlap() {
+        let state = McpState {
+            clients: Mutex::default(),
+        };
+        let spans = Spans::default();
+        register(&state, "a", &spans).await;
+        register(&state, "b", &spans).await;
+
+        tokio::join!(call(&state, "a"), call(&state, "b"));
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 2);
+        let (first, second) = (spans[0], spans[1]);
+        assert!(
+            first.0 < second.1 && second.0 < first.1,
+            "calls ran one after the other: {spans:?}"
+        );
     }
 }
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Requests parked until the webview answers them, keyed by a generated id.
+pub struct PendingReplies<T> {
+    next_id: AtomicU64,
+    waiting: Mutex<HashMap<String, oneshot::Sender<T>>>,
+}
+
+impl<T> Default for PendingReplies<T> {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            waiting: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T> PendingReplies<T> {
+    /// Registers a request, hands its id to `notify` (usually to emit an event), and waits
+    /// for the reply. Returns `None` if nobody answers within `timeout`.
+    pub async fn ask(&self, notify: impl FnOnce(&str), timeout: Duration) -> Option<T> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed).to_string();
+        let (tx, rx) = oneshot::channel();
+        self.waiting.lock().unwrap().insert(id.clone(), tx);
+        // Also forgets the request when the caller stops waiting, e.g. a cancelled tool call.
+        let _waiting = Waiting { replies: self, id };
+
+        notify(&_waiting.id);
+
+        let reply = tokio::time::timeout(timeout, rx).await;
+        reply.ok().and_then(Result::ok)
+    }
+
+    /// Delivers the reply for `id`, returning `false` if it is unknown or already answered.
+    pub fn resolve(&self, id: &str, reply: T) -> bool {
+        let sender = self.waiting.lock().unwrap().remove(id);
+        sender.is_some_and(|sender| sender.send(reply).is_ok())
+    }
+}
+
+struct Waiting<'a, T> {
+    replies: &'a PendingReplies<T>,
+    id: String,
+}
+
+impl<T> Drop for Waiting<'_, T> {
+    fn drop(&mut self) {
+        self.replies.waiting.lock().unwrap().remove(&self.id);
+    }
+}
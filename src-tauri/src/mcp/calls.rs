@@ -0,0 +1,260 @@
+use rmcp::model::{
+    CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotificationParam,
+    ClientRequest, Meta, NumberOrString, ProgressNotificationParam, ProgressToken, ServerResult,
+};
+use rmcp::service::{PeerRequestOptions, RequestHandle};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
+
+use super::catalog::CatalogState;
+use super::output::ToolOutput;
+use super::policy::{self, PolicyAction, PolicyState, ToolApprovalEvent};
+use super::{schema, McpClient, McpState};
+
+#[derive(Clone, Serialize)]
+pub struct ProgressUpdate {
+    pub progress: f64,
+    pub total: Option<f64>,
+    pub message: Option<String>,
+}
+
+/// Identifies an in-flight call by chat session and the model's tool-call id. Providers reuse
+/// ids like `call_0` across chats, so the id alone isn't unique.
+pub type CallKey = (Option<String>, String);
+
+/// Bookkeeping for in-flight tool calls: where to stream progress and how to cancel them.
+#[derive(Default)]
+pub struct CallsState {
+    /// Progress channels keyed by server id and progress token.
+    progress: Mutex<HashMap<(String, String), Channel<ProgressUpdate>>>,
+    cancels: Mutex<HashMap<CallKey, oneshot::Sender<()>>>,
+}
+
+impl CallsState {
+    pub fn report_progress(&self, server_id: &str, params: ProgressNotificationParam) {
+        let key = (server_id.to_string(), token_key(&params.progress_token));
+        if let Some(channel) = self.progress.lock().unwrap().get(&key) {
+            let _ = channel.send(ProgressUpdate {
+                progress: params.progress,
+                total: params.total,
+                message: params.message,
+            });
+        }
+    }
+
+    /// Makes a call cancellable through [`CallsState::cancel`] until it is unregistered.
+    fn register(&self, call: &CallKey) -> oneshot::Receiver<()> {
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        self.cancels.lock().unwrap().insert(call.clone(), cancel_tx);
+        cancel_rx
+    }
+
+    fn unregister(&self, call: &CallKey) {
+        self.cancels.lock().unwrap().remove(call);
+    }
+
+    /// Signals the call registered under `call`, returning whether it was still running.
+    pub fn cancel(&self, call: &CallKey) -> bool {
+        let sender = self.cancels.lock().unwrap().remove(call);
+        sender.is_some_and(|sender| sender.send(()).is_ok())
+    }
+}
+
+/// Where a call comes from, for the policy engine.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallContext {
+    pub session_id: Option<String>,
+    pub project_id: Option<String>,
+}
+
+/// Validates a call against the tool's schema, applies the tool policy, and dispatches it.
+/// Calls stopped before dispatch come back as error results the model can read.
+pub async fn run(
+    app: &AppHandle,
+    server_id: &str,
+    name: String,
+    args: Value,
+    call_id: Option<String>,
+    on_progress: Option<Channel<ProgressUpdate>>,
+    context: &CallContext,
+) -> Result<ToolOutput, String> {
+    // Registered before anything else so the call can be stopped while it waits for approval.
+    let calls = app.state::<CallsState>();
+    let call = call_id.map(|call_id| (context.session_id.clone(), call_id));
+    let cancelled = call.as_ref().map(|call| calls.register(call));
+
+    let result = check_and_call(app, server_id, name, args, cancelled, on_progress, context).await;
+
+    if let Some(call) = &call {
+        calls.unregister(call);
+    }
+    result
+}
+
+async fn check_and_call(
+    app: &AppHandle,
+    server_id: &str,
+    name: String,
+    args: Value,
+    mut cancelled: Option<oneshot::Receiver<()>>,
+    on_progress: Option<Channel<ProgressUpdate>>,
+    context: &CallContext,
+) -> Result<ToolOutput, String> {
+    let state = app.state::<McpState>();
+    // For results made up here rather than by the server
+    let refused = |result| ToolOutput::new(result, server_id, None);
+
+    // A missing argument object is the same as an empty one as far as the schema goes.
+    let args = if args.is_null() {
+        Value::Object(Default::default())
+    } else {
+        args
+    };
+
+    // Non-object arguments are rejected even when the tool's schema isn't known.
+    if let Err(violations) = schema::check_object(&args) {
+        return Ok(refused(schema::invalid_arguments(&name, &violations)));
+    }
+
+    let tools = app.state::<CatalogState>().tools(&state, server_id).await?;
+    let tool = tools.iter().find(|t| t.name == name);
+    if let Some(tool) = tool {
+        if let Err(violations) = schema::check_arguments(tool, &args) {
+            return Ok(refused(schema::invalid_arguments(&name, &violations)));
+        }
+    }
+
+    let policies = app.state::<PolicyState>();
+    let action = match policies.evaluate(app, context.project_id.as_deref(), server_id, &name, tool)
+    {
+        Ok(action) => action,
+        Err(e) => {
+            let reason = format!("the tool policy could not be read ({e})");
+            return Ok(refused(policy::denied(&name, &reason)));
+        }
+    };
+    match action {
+        PolicyAction::Allow => {}
+        PolicyAction::Deny => {
+            return Ok(refused(policy::denied(&name, "blocked by the tool policy")))
+        }
+        PolicyAction::Ask => {
+            let approval = policies.approvals.ask(
+                |request_id| {
+                    let event = ToolApprovalEvent {
+                        request_id,
+                        server_id,
+                        tool: &name,
+                        arguments: &args,
+                        session_id: context.session_id.as_deref(),
+                    };
+                    let _ = app.emit(policy::TOOL_APPROVAL_EVENT, event);
+                },
+                policy::APPROVAL_TIMEOUT,
+            );
+            let approved = match &mut cancelled {
+                Some(cancelled) => tokio::select! {
+                    approved = approval => approved,
+                    Ok(()) = cancelled => return Err("Tool call cancelled".to_string()),
+                },
+                None => approval.await,
+            };
+            if approved != Some(true) {
+                return Ok(refused(policy::denied(
+                    &name,
+                    "the user did not approve it",
+                )));
+            }
+        }
+    }
+
+    let client = state.client(server_id).await?;
+    let param = CallToolRequestParam {
+        name: name.into(),
+        arguments: args.as_object().cloned(),
+    };
+    let calls = app.state::<CallsState>();
+    let result = call_tool(&client, &calls, server_id, cancelled, on_progress, param).await?;
+    Ok(ToolOutput::new(result, server_id, tool))
+}
+
+fn token_key(token: &ProgressToken) -> String {
+    match &token.0 {
+        NumberOrString::Number(n) => n.to_string(),
+        NumberOrString::String(s) => s.to_string(),
+    }
+}
+
+/// Calls a tool, streaming progress to `on_progress` and stopping early if `cancelled` fires.
+/// Each call gets its own progress token.
+pub async fn call_tool(
+    client: &McpClient,
+    calls: &CallsState,
+    server_id: &str,
+    cancelled: Option<oneshot::Receiver<()>>,
+    on_progress: Option<Channel<ProgressUpdate>>,
+    param: CallToolRequestParam,
+) -> Result<CallToolResult, String> {
+    let Some(cancelled) = cancelled else {
+        return client.call_tool(param).await.map_err(|e| e.to_string());
+    };
+
+    let token = uuid::Uuid::new_v4().to_string();
+    let progress_key = (server_id.to_string(), token.clone());
+    if let Some(channel) = on_progress {
+        calls
+            .progress
+            .lock()
+            .unwrap()
+            .insert(progress_key.clone(), channel);
+    }
+
+    let result = run_cancellable(client, &token, cancelled, param).await;
+
+    calls.progress.lock().unwrap().remove(&progress_key);
+    result
+}
+
+async fn run_cancellable(
+    client: &McpClient,
+    progress_token: &str,
+    cancelled: oneshot::Receiver<()>,
+    param: CallToolRequestParam,
+) -> Result<CallToolResult, String> {
+    let mut meta = Meta::new();
+    meta.set_progress_token(ProgressToken(NumberOrString::String(progress_token.into())));
+    let options = PeerRequestOptions {
+        timeout: None,
+        meta: Some(meta),
+    };
+
+    let request = ClientRequest::CallToolRequest(CallToolRequest::new(param));
+    let RequestHandle { rx, id, peer, .. } = client
+        .send_request_with_option(request, options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tokio::select! {
+        response = rx => match response {
+            Ok(Ok(ServerResult::CallToolResult(result))) => Ok(result),
+            Ok(Ok(_)) => Err("Unexpected response to tools/call".to_string()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err("Transport closed".to_string()),
+        },
+        Ok(()) = cancelled => {
+            let _ = peer
+                .notify_cancelled(CancelledNotificationParam {
+                    request_id: id,
+                    reason: Some("Cancelled by user".to_string()),
+                })
+                .await;
+            Err("Tool call cancelled".to_string())
+        }
+    }
+}
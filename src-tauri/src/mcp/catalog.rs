@@ -0,0 +1,248 @@
+use rmcp::model::Tool;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::McpState;
+
+pub const TOOLS_CHANGED_EVENT: &str = "mcp://tools-changed";
+
+/// Longest tool name the chat completion providers accept.
+const MAX_TOOL_NAME_LEN: usize = 64;
+const SEPARATOR: &str = "__";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub server_id: String,
+    pub tools: Vec<CatalogTool>,
+    /// Set instead of `tools` being populated when the server couldn't be listed.
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogTool {
+    /// Name unique across every server in the catalog, safe to hand to the model.
+    pub qualified_name: String,
+    #[serde(flatten)]
+    pub tool: Tool,
+}
+
+#[derive(Default)]
+struct ServerTools {
+    /// Bumped on every invalidation so a listing that raced with one isn't cached.
+    generation: u64,
+    tools: Option<Arc<Vec<Tool>>>,
+}
+
+/// Per-server cache of every tool page, dropped whenever a server reports its tool list
+/// changed or its session is replaced.
+#[derive(Default)]
+pub struct CatalogState {
+    servers: Mutex<HashMap<String, ServerTools>>,
+    /// Qualified names handed out so far, mapped to server id and tool name. Entries are kept
+    /// between catalogs so a chat still holding an older catalog can route its calls.
+    routes: Mutex<HashMap<String, (String, String)>>,
+}
+
+impl CatalogState {
+    pub fn invalidate(&self, server_id: &str) {
+        let mut servers = self.servers.lock().unwrap();
+        let entry = servers.entry(server_id.to_string()).or_default();
+        entry.generation += 1;
+        entry.tools = None;
+    }
+
+    /// Returns the cached tools for `server_id`, listing every page from the server on a miss.
+    pub async fn tools(&self, state: &McpState, server_id: &str) -> Result<Arc<Vec<Tool>>, String> {
+        let generation = {
+            let mut servers = self.servers.lock().unwrap();
+            let entry = servers.entry(server_id.to_string()).or_default();
+            if let Some(tools) = &entry.tools {
+                return Ok(tools.clone());
+            }
+            entry.generation
+        };
+
+        let client = state.client(server_id).await?;
+        let tools = Arc::new(client.list_all_tools().await.map_err(|e| e.to_string())?);
+
+        let mut servers = self.servers.lock().unwrap();
+        if let Some(entry) = servers.get_mut(server_id) {
+            if entry.generation == generation {
+                entry.tools = Some(tools.clone());
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Resolves a qualified tool name from the catalog back to its server id and tool name.
+    pub fn route(&self, qualified_name: &str) -> Option<(String, String)> {
+        self.routes.lock().unwrap().get(qualified_name).cloned()
+    }
+
+    /// Collects the tools of the given servers, or of every connected server when `ids` is `None`,
+    /// giving each a qualified name that routes back to its server.
+    pub async fn entries(&self, state: &McpState, ids: Option<Vec<String>>) -> Vec<CatalogEntry> {
+        let names: HashMap<String, String> = {
+            let clients = state.clients.lock().await;
+            clients
+                .iter()
+                .map(|(id, connection)| {
+                    let name = connection.config.name.clone().unwrap_or_default();
+                    (id.clone(), name)
+                })
+                .collect()
+        };
+        let ids = ids.unwrap_or_else(|| names.keys().cloned().collect());
+
+        let listings = ids.into_iter().map(|server_id| async move {
+            let tools = self.tools(state, &server_id).await;
+            (server_id, tools)
+        });
+        let listings = futures::future::join_all(listings).await;
+
+        let mut routes = self.routes.lock().unwrap();
+        listings
+            .into_iter()
+            .map(|(server_id, tools)| match tools {
+                Ok(tools) => {
+                    let prefix = names
+                        .get(&server_id)
+                        .filter(|name| !name.trim().is_empty())
+                        .unwrap_or(&server_id);
+                    let tools = tools
+                        .iter()
+                        .map(|tool| {
+                            let qualified_name =
+                                unique_name(&routes, prefix, &server_id, &tool.name);
+                            routes.insert(
+                                qualified_name.clone(),
+                                (server_id.clone(), tool.name.to_string()),
+                            );
+                            CatalogTool {
+                                qualified_name,
+                                tool: tool.clone(),
+                            }
+                        })
+                        .collect();
+                    CatalogEntry {
+                        server_id,
+                        tools,
+                        error: None,
+                    }
+                }
+                Err(error) => CatalogEntry {
+                    server_id,
+                    tools: Vec::new(),
+                    error: Some(error),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Builds `server__tool` from sanitized parts, falling back to a hashed suffix when that name
+/// is too long for providers or already taken by a server with a similar name.
+fn unique_name(
+    taken: &HashMap<String, (String, String)>,
+    prefix: &str,
+    server_id: &str,
+    tool: &str,
+) -> String {
+    let name = format!("{}{SEPARATOR}{}", sanitize(prefix), sanitize(tool));
+    let available = match taken.get(&name) {
+        Some((id, name)) => id == server_id && name == tool,
+        None => true,
+    };
+    if name.len() <= MAX_TOOL_NAME_LEN && available {
+        return name;
+    }
+
+    let suffix = format!("_{:08x}", fnv1a(&format!("{server_id}/{tool}")));
+    let mut name = name;
+    name.truncate(MAX_TOOL_NAME_LEN - suffix.len());
+    name + &suffix
+}
+
+/// Providers only accept `[A-Za-z0-9_-]` in tool names.
+fn sanitize(part: &str) -> String {
+    part.trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Small stable hash so the same tool keeps the same name across catalogs and restarts.
+fn fnv1a(value: &str) -> u32 {
+    value.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn taken(entries: &[(&str, &str, &str)]) -> HashMap<String, (String, String)> {
+        entries
+            .iter()
+            .map(|(name, id, tool)| (name.to_string(), (id.to_string(), tool.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn sanitize_replaces_characters_providers_reject() {
+        assert_eq!(sanitize(" my server.v2 "), "my_server_v2");
+        assert_eq!(sanitize("read-file_1"), "read-file_1");
+        assert_eq!(sanitize("café"), "caf_");
+    }
+
+    #[test]
+    fn plain_names_are_kept() {
+        let name = unique_name(&HashMap::new(), "files", "s1", "read file");
+        assert_eq!(name, "files__read_file");
+
+        // A tool keeps its name when it is listed again.
+        let taken = taken(&[("files__read_file", "s1", "read file")]);
+        assert_eq!(unique_name(&taken, "files", "s1", "read file"), name);
+    }
+
+    #[test]
+    fn collisions_get_a_stable_suffix() {
+        // Another server with the same prefix, and a tool that only differs before sanitizing.
+        let taken = taken(&[("files__read_file", "s1", "read file")]);
+        let other_server = unique_name(&taken, "files", "s2", "read file");
+        let other_tool = unique_name(&taken, "files", "s1", "read.file");
+
+        for name in [&other_server, &other_tool] {
+            assert_ne!(name, "files__read_file");
+            assert!(name.starts_with("files__read_file_"), "{name}");
+            assert_eq!(name.len(), "files__read_file".len() + 9);
+        }
+        assert_ne!(other_server, other_tool);
+        assert_eq!(
+            unique_name(&taken, "files", "s2", "read file"),
+            other_server
+        );
+    }
+
+    #[test]
+    fn long_names_are_truncated_to_the_limit() {
+        let long = "x".repeat(100);
+        let first = unique_name(&HashMap::new(), "files", "s1", &long);
+        let second = unique_name(&HashMap::new(), "files", "s1", &format!("{long}y"));
+
+        assert_eq!(first.len(), MAX_TOOL_NAME_LEN);
+        assert_eq!(second.len(), MAX_TOOL_NAME_LEN);
+        assert!(first.starts_with("files__xxx"));
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,106 @@
+use rmcp::model::{ElicitationAction, ElicitationSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::approval::PendingReplies;
+
+pub const ELICITATION_REQUEST_EVENT: &str = "mcp://elicitation-request";
+
+/// How long the form stays open before the request is cancelled on the user's behalf.
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The user's answer to an elicitation form.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElicitationReply {
+    pub action: ElicitationAction,
+    pub content: Option<Value>,
+}
+
+#[derive(Default)]
+pub struct ElicitationState {
+    pub replies: PendingReplies<ElicitationReply>,
+    /// Schemas of the open requests, so answers can be checked before they are delivered.
+    pub schemas: Mutex<HashMap<String, ElicitationSchema>>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationRequestEvent<'a> {
+    pub request_id: &'a str,
+    pub server_id: &'a str,
+    pub message: &'a str,
+    pub requested_schema: &'a ElicitationSchema,
+}
+
+/// Rejects schemas the form can't honour, e.g. required fields that have no definition.
+/// A schema without properties is a plain accept/decline confirmation.
+pub fn check_schema(schema: &ElicitationSchema) -> Result<(), String> {
+    for name in schema.required.iter().flatten() {
+        if !schema.properties.contains_key(name) {
+            return Err(format!("Required field '{name}' is not defined"));
+        }
+    }
+    Ok(())
+}
+
+/// Checks accepted content against the requested schema, listing every problem found.
+/// Fields the form didn't ask for are rejected too.
+pub fn validate_content(schema: &ElicitationSchema, content: &Value) -> Result<(), Vec<String>> {
+    let Ok(Value::Object(mut schema)) = serde_json::to_value(schema) else {
+        return Err(vec!["Elicitation schema could not be read".to_string()]);
+    };
+    schema
+        .entry("additionalProperties")
+        .or_insert(Value::Bool(false));
+    super::schema::validate(&schema, content, "content")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(value: Value) -> ElicitationSchema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn empty_schema_is_a_confirmation() {
+        let confirm = schema(json!({ "type": "object", "properties": {} }));
+        assert!(check_schema(&confirm).is_ok());
+        assert!(validate_content(&confirm, &json!({})).is_ok());
+        assert!(validate_content(&confirm, &json!({ "extra": 1 })).is_err());
+    }
+
+    #[test]
+    fn required_fields_must_be_defined() {
+        let broken = schema(json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "required": ["email"]
+        }));
+        assert!(check_schema(&broken).is_err());
+    }
+
+    #[test]
+    fn content_is_checked_against_the_schema() {
+        let form = schema(json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "minLength": 2 },
+                "age": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["name"]
+        }));
+        assert!(validate_content(&form, &json!({ "name": "Ada", "age": 36 })).is_ok());
+
+        let errors = validate_content(&form, &json!({ "age": -1, "extra": true })).unwrap_err();
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(validate_content(&form, &json!({ "name": "A" })).is_err());
+        assert!(validate_content(&form, &json!({ "name": "Ada", "age": 1.5 })).is_err());
+        assert!(validate_content(&form, &json!("Ada")).is_err());
+    }
+}
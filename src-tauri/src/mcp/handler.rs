@@ -0,0 +1,200 @@
+use rmcp::model::{
+    ClientCapabilities, ClientInfo, CreateElicitationRequestParam, CreateElicitationResult,
+    CreateMessageRequestParam, CreateMessageResult, ElicitationAction, ElicitationCapability,
+    ErrorCode, Implementation, JsonObject, ListRootsResult, ProgressNotificationParam,
+    ResourceUpdatedNotificationParam, RootsCapabilities,
+};
+use rmcp::service::{NotificationContext, RequestContext, RoleClient};
+use rmcp::{ClientHandler, ErrorData as McpError};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::calls::CallsState;
+use super::catalog::{CatalogState, TOOLS_CHANGED_EVENT};
+use super::elicitation::{self, ElicitationRequestEvent, ElicitationState};
+use super::roots::RootsState;
+use super::sampling::{self, SamplingRequestEvent, SamplingState};
+
+pub const RESOURCE_UPDATED_EVENT: &str = "mcp://resource-updated";
+pub const RESOURCE_LIST_CHANGED_EVENT: &str = "mcp://resource-list-changed";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceUpdatedEvent<'a> {
+    id: &'a str,
+    uri: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerEvent<'a> {
+    id: &'a str,
+}
+
+/// Client side of an MCP session, forwarding server-initiated traffic to the webview.
+pub struct McpHandler {
+    app: AppHandle,
+    server_id: String,
+}
+
+impl McpHandler {
+    pub fn new(app: AppHandle, server_id: String) -> Self {
+        Self { app, server_id }
+    }
+}
+
+impl ClientHandler for McpHandler {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        let state = self.app.state::<SamplingState>();
+        let config = state.config.read().unwrap().clone().ok_or_else(|| {
+            McpError::internal_error("Sampling is not configured in c-chat", None)
+        })?;
+
+        let approved = state
+            .approvals
+            .ask(
+                |request_id| {
+                    let event = SamplingRequestEvent {
+                        request_id,
+                        server_id: &self.server_id,
+                        model: &config.model,
+                        system_prompt: params.system_prompt.as_deref(),
+                        messages: &params.messages,
+                        max_tokens: params.max_tokens,
+                    };
+                    let _ = self.app.emit(sampling::SAMPLING_REQUEST_EVENT, event);
+                },
+                sampling::APPROVAL_TIMEOUT,
+            )
+            .await
+            .unwrap_or(false);
+        if !approved {
+            return Err(McpError::new(
+                ErrorCode(-1),
+                "User rejected sampling request",
+                None,
+            ));
+        }
+
+        sampling::complete(&config, &params)
+            .await
+            .map_err(|e| McpError::internal_error(e, None))
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, McpError> {
+        let roots = self.app.state::<RootsState>().get(&self.server_id);
+        Ok(ListRootsResult { roots })
+    }
+
+    async fn create_elicitation(
+        &self,
+        request: CreateElicitationRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, McpError> {
+        elicitation::check_schema(&request.requested_schema)
+            .map_err(|e| McpError::invalid_params(e, None))?;
+
+        let state = self.app.state::<ElicitationState>();
+        let mut pending_id = String::new();
+        let reply = state
+            .replies
+            .ask(
+                |request_id| {
+                    pending_id = request_id.to_string();
+                    state
+                        .schemas
+                        .lock()
+                        .unwrap()
+                        .insert(request_id.to_string(), request.requested_schema.clone());
+                    let event = ElicitationRequestEvent {
+                        request_id,
+                        server_id: &self.server_id,
+                        message: &request.message,
+                        requested_schema: &request.requested_schema,
+                    };
+                    let _ = self.app.emit(elicitation::ELICITATION_REQUEST_EVENT, event);
+                },
+                elicitation::RESPONSE_TIMEOUT,
+            )
+            .await;
+        state.schemas.lock().unwrap().remove(&pending_id);
+
+        Ok(match reply {
+            Some(reply) => CreateElicitationResult {
+                action: reply.action,
+                content: reply.content,
+            },
+            None => CreateElicitationResult {
+                action: ElicitationAction::Cancel,
+                content: None,
+            },
+        })
+    }
+
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.app.state::<CatalogState>().invalidate(&self.server_id);
+        let _ = self.app.emit(
+            TOOLS_CHANGED_EVENT,
+            ServerEvent {
+                id: &self.server_id,
+            },
+        );
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.app
+            .state::<CallsState>()
+            .report_progress(&self.server_id, params);
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let event = ResourceUpdatedEvent {
+            id: &self.server_id,
+            uri: params.uri,
+        };
+        let _ = self.app.emit(RESOURCE_UPDATED_EVENT, event);
+    }
+
+    async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        let event = ServerEvent {
+            id: &self.server_id,
+        };
+        let _ = self.app.emit(RESOURCE_LIST_CHANGED_EVENT, event);
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            client_info: Implementation {
+                name: "c-chat".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Implementation::default()
+            },
+            capabilities: ClientCapabilities {
+                sampling: Some(JsonObject::default()),
+                roots: Some(RootsCapabilities {
+                    list_changed: Some(true),
+                }),
+                elicitation: Some(ElicitationCapability {
+                    schema_validation: Some(true),
+                }),
+                ..ClientCapabilities::default()
+            },
+            ..ClientInfo::default()
+        }
+    }
+}
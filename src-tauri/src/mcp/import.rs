@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use super::{McpState, ServerConfig, TransportType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    /// Connected under `id`; the frontend should add it to the settings.
+    Imported,
+    /// Left out on purpose: disabled in the file, or a server with that name already exists.
+    Skipped,
+    /// Invalid, or valid but the connection failed.
+    Failed,
+}
+
+/// What happened to one entry of an `mcpServers` file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub name: String,
+    pub status: ImportStatus,
+    pub id: Option<String>,
+    /// The entry mapped onto our config, when it was valid.
+    pub config: Option<ServerConfig>,
+    pub transport: Option<TransportType>,
+    pub message: Option<String>,
+}
+
+impl ImportReport {
+    fn new(name: &str, status: ImportStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            id: None,
+            config: None,
+            transport: None,
+            message: Some(message.into()),
+        }
+    }
+}
+
+/// An entry in the format other desktop clients use, either
+/// `{ "command", "args", "env", "cwd" }` or `{ "url", "headers" }`, with an optional `type`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalServer {
+    #[serde(alias = "transport")]
+    r#type: Option<String>,
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    url: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+/// Pulls the server entries out of a config file, sorted by name. Accepts the
+/// `mcpServers` object, the `servers` object some editors use, or a bare map of servers.
+fn entries(json: &str) -> Result<Vec<(String, Value)>, String> {
+    let root: Value = serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {e}"))?;
+    let servers = root
+        .get("mcpServers")
+        .or_else(|| root.get("servers"))
+        .unwrap_or(&root);
+    let servers = servers
+        .as_object()
+        .ok_or("Expected an object of servers under `mcpServers`")?;
+    Ok(servers
+        .iter()
+        .map(|(name, entry)| (name.clone(), entry.clone()))
+        .collect())
+}
+
+fn transport(kind: Option<&str>) -> Result<Option<TransportType>, String> {
+    Ok(match kind.map(str::to_ascii_lowercase).as_deref() {
+        None => None,
+        Some("stdio") => Some(TransportType::Stdio),
+        Some("sse") => Some(TransportType::Sse),
+        Some("http" | "streamable-http" | "streamablehttp" | "streamable_http") => {
+            Some(TransportType::StreamableHttp)
+        }
+        Some("ws" | "websocket") => Some(TransportType::WebSocket),
+        Some(other) => return Err(format!("Unsupported transport type `{other}`")),
+    })
+}
+
+/// Checks an entry and maps it onto our config. `Ok(None)` means the entry is disabled.
+fn to_config(name: &str, entry: Value) -> Result<Option<ServerConfig>, String> {
+    let entry: ExternalServer = serde_json::from_value(entry).map_err(|e| e.to_string())?;
+    if entry.disabled {
+        return Ok(None);
+    }
+
+    let transport = transport(entry.r#type.as_deref())?;
+    let command = entry.command.filter(|c| !c.trim().is_empty());
+    let url = entry.url.filter(|u| !u.trim().is_empty());
+    let transport = match (&command, &url) {
+        (Some(_), Some(_)) => return Err("Has both `command` and `url`".to_string()),
+        (None, None) => return Err("Needs either `command` or `url`".to_string()),
+        (Some(_), None) => match transport {
+            None | Some(TransportType::Stdio) => TransportType::Stdio,
+            Some(_) => return Err("`command` is only valid for stdio servers".to_string()),
+        },
+        (None, Some(url)) => {
+            let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL: {e}"))?;
+            let websocket = matches!(parsed.scheme(), "ws" | "wss");
+            match transport {
+                Some(TransportType::Stdio) => {
+                    return Err("Stdio servers need a `command`".to_string())
+                }
+                Some(TransportType::WebSocket) | None if websocket => TransportType::WebSocket,
+                _ if websocket => {
+                    return Err(format!(
+                        "`{}` URLs need the websocket type",
+                        parsed.scheme()
+                    ))
+                }
+                Some(TransportType::WebSocket) => {
+                    return Err("WebSocket servers need a ws:// or wss:// URL".to_string())
+                }
+                Some(transport) => transport,
+                None => TransportType::Auto,
+            }
+        }
+    };
+
+    Ok(Some(ServerConfig {
+        name: Some(name.to_string()),
+        transport: Some(transport),
+        url,
+        command,
+        args: entry.args,
+        cwd: entry.cwd,
+        env: entry.env,
+        headers: entry.headers,
+        ..Default::default()
+    }))
+}
+
+/// Imports every server in an `mcpServers` config, connecting the valid ones concurrently.
+/// Entries whose name matches one in `existing` are skipped so re-importing a file is harmless.
+pub async fn import(
+    app: &AppHandle,
+    state: &McpState,
+    json: &str,
+    existing: &[String],
+) -> Result<Vec<ImportReport>, String> {
+    let imports = entries(json)?.into_iter().map(|(name, entry)| async move {
+        if existing
+            .iter()
+            .any(|e| e.trim().eq_ignore_ascii_case(name.trim()))
+        {
+            return ImportReport::new(
+                &name,
+                ImportStatus::Skipped,
+                "A server with this name exists",
+            );
+        }
+        let config = match to_config(&name, entry) {
+            Ok(Some(config)) => config,
+            Ok(None) => return ImportReport::new(&name, ImportStatus::Skipped, "Disabled"),
+            Err(e) => return ImportReport::new(&name, ImportStatus::Failed, e),
+        };
+
+        let id = uuid::Uuid::new_v4().to_string();
+        match super::connect_or_authorize(app, &id, &config).await {
+            Ok((client, transport)) => {
+                state
+                    .register(app, id.clone(), config.clone(), client, transport)
+                    .await;
+                ImportReport {
+                    name,
+                    status: ImportStatus::Imported,
+                    id: Some(id),
+                    config: Some(config),
+                    transport: Some(transport),
+                    message: None,
+                }
+            }
+            Err(e) => ImportReport {
+                config: Some(config),
+                ..ImportReport::new(&name, ImportStatus::Failed, e)
+            },
+        }
+    });
+    Ok(futures::future::join_all(imports).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(entry: Value) -> Result<Option<ServerConfig>, String> {
+        to_config("server", entry)
+    }
+
+    #[test]
+    fn stdio_entries_keep_their_command_line() {
+        let config = config(json!({
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+            "env": { "DEBUG": "1" },
+            "cwd": "/tmp"
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.name.as_deref(), Some("server"));
+        assert_eq!(config.transport, Some(TransportType::Stdio));
+        assert_eq!(config.command.as_deref(), Some("npx"));
+        assert_eq!(config.args.len(), 2);
+        assert_eq!(config.env["DEBUG"], "1");
+        assert_eq!(config.cwd.as_deref(), Some("/tmp"));
+    }
+
+    #[test]
+    fn url_entries_pick_their_transport() {
+        let transport = |entry| config(entry).unwrap().unwrap().transport;
+        assert_eq!(
+            transport(json!({ "url": "https://example.com/mcp" })),
+            Some(TransportType::Auto)
+        );
+        assert_eq!(
+            transport(json!({ "url": "https://example.com/sse", "type": "sse" })),
+            Some(TransportType::Sse)
+        );
+        assert_eq!(
+            transport(json!({ "url": "https://example.com/mcp", "transport": "streamable-http" })),
+            Some(TransportType::StreamableHttp)
+        );
+        assert_eq!(
+            transport(json!({ "url": "wss://example.com/mcp" })),
+            Some(TransportType::WebSocket)
+        );
+    }
+
+    #[test]
+    fn disabled_entries_are_skipped() {
+        assert!(config(json!({ "command": "npx", "disabled": true }))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn inconsistent_entries_are_rejected() {
+        for entry in [
+            json!({}),
+            json!({ "command": " " }),
+            json!({ "command": "npx", "url": "https://example.com/mcp" }),
+            json!({ "command": "npx", "type": "sse" }),
+            json!({ "url": "https://example.com/mcp", "type": "stdio" }),
+            json!({ "url": "https://example.com/mcp", "type": "websocket" }),
+            json!({ "url": "ws://example.com/mcp", "type": "http" }),
+            json!({ "url": "not a url" }),
+            json!({ "url": "https://example.com/mcp", "type": "grpc" }),
+            json!({ "command": "npx", "args": "-y" }),
+        ] {
+            assert!(config(entry.clone()).is_err(), "{entry}");
+        }
+    }
+}
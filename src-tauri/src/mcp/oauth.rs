@@ -0,0 +1,285 @@
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use rmcp::transport::auth::{
+    AuthError, AuthorizationManager, CredentialStore, OAuthState, StoredCredentials,
+};
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use super::{http_client, secrets, ServerConfig, TransportType};
+
+/// How long we wait for the user to finish signing in before giving up.
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+const CALLBACK_PAGE: &str = "<!doctype html><html><body style=\"font-family: sans-serif\">\
+    <h3>Authorization complete</h3><p>You can close this window and return to c-chat.</p>\
+    </body></html>";
+
+/// Keeps a server's OAuth client id and tokens in the system keychain so they survive restarts.
+struct KeychainCredentialStore {
+    name: String,
+}
+
+impl KeychainCredentialStore {
+    fn new(server_id: &str) -> Self {
+        Self {
+            name: format!("oauth/{server_id}"),
+        }
+    }
+}
+
+/// Runs a keychain call off the async runtime, as the platform stores can block.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, AuthError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AuthError::InternalError(e.to_string()))?
+        .map_err(AuthError::InternalError)
+}
+
+#[async_trait]
+impl CredentialStore for KeychainCredentialStore {
+    async fn load(&self) -> Result<Option<StoredCredentials>, AuthError> {
+        let name = self.name.clone();
+        match blocking(move || secrets::find(&name)).await? {
+            Some(data) => serde_json::from_str(&data)
+                .map(Some)
+                .map_err(|e| AuthError::InternalError(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, credentials: StoredCredentials) -> Result<(), AuthError> {
+        let data = serde_json::to_string(&credentials)
+            .map_err(|e| AuthError::InternalError(e.to_string()))?;
+        let name = self.name.clone();
+        blocking(move || secrets::set(&name, &data)).await
+    }
+
+    async fn clear(&self) -> Result<(), AuthError> {
+        let name = self.name.clone();
+        blocking(move || secrets::delete(&name)).await
+    }
+}
+
+/// Deletes a server's stored sign-in, e.g. when the server is removed.
+pub async fn forget(server_id: &str) -> Result<(), String> {
+    KeychainCredentialStore::new(server_id)
+        .clear()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Restores the authorization for a server from a previous sign-in, if there is one.
+/// Expired access tokens are refreshed by the manager when the transport asks for them.
+pub async fn stored_authorization(
+    server_id: &str,
+    url: &str,
+) -> Result<Option<AuthorizationManager>, String> {
+    let store = KeychainCredentialStore::new(server_id);
+    if store.load().await.map_err(|e| e.to_string())?.is_none() {
+        return Ok(None);
+    }
+
+    let mut manager = AuthorizationManager::new(url)
+        .await
+        .map_err(|e| e.to_string())?;
+    manager.set_credential_store(store);
+    match manager.initialize_from_store().await {
+        Ok(true) => Ok(Some(manager)),
+        Ok(false) => Ok(None),
+        Err(e) => Err(format!("Failed to restore MCP authorization: {e}")),
+    }
+}
+
+/// Checks whether an HTTP server turns us away for lack of credentials.
+pub async fn requires_authorization(config: &ServerConfig) -> bool {
+    let Ok(url) = config.url() else {
+        return false;
+    };
+    let Ok(client) = http_client(config).await else {
+        return false;
+    };
+
+    let request = match config.transport_type() {
+        TransportType::Sse => client.get(url).header("Accept", "text/event-stream"),
+        _ => client
+            .post(url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(&serde_json::json!({ "jsonrpc": "2.0", "id": 0, "method": "ping" })),
+    };
+    match request.send().await {
+        Ok(response) => response.status() == StatusCode::UNAUTHORIZED,
+        Err(_) => false,
+    }
+}
+
+/// Runs the spec's authorization code flow for a server: metadata discovery, dynamic client
+/// registration and PKCE, with the browser redirecting back to a loopback listener.
+pub async fn authorize(app: &AppHandle, server_id: &str, url: &str) -> Result<(), String> {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let redirect_uri = format!("http://127.0.0.1:{port}/callback");
+
+    let mut manager = AuthorizationManager::new(url)
+        .await
+        .map_err(|e| e.to_string())?;
+    manager.set_credential_store(KeychainCredentialStore::new(server_id));
+
+    let mut oauth = OAuthState::Unauthorized(manager);
+    oauth
+        .start_authorization(&[], &redirect_uri, Some("c-chat"))
+        .await
+        .map_err(|e| format!("MCP authorization failed: {e}"))?;
+    let auth_url = oauth
+        .get_authorization_url()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let state = url::Url::parse(&auth_url)
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(key, _)| key == "state")
+                .map(|(_, value)| value.into_owned())
+        })
+        .ok_or("MCP authorization URL has no state parameter")?;
+    app.opener()
+        .open_url(auth_url, None::<&str>)
+        .map_err(|e| e.to_string())?;
+
+    let callback = wait_for_callback(listener, &state);
+    let (code, csrf_token) = tokio::time::timeout(CALLBACK_TIMEOUT, callback)
+        .await
+        .map_err(|_| "Timed out waiting for MCP authorization".to_string())??;
+
+    oauth
+        .handle_callback(&code, &csrf_token)
+        .await
+        .map_err(|e| format!("MCP authorization failed: {e}"))
+}
+
+/// Accepts connections on the loopback listener until the redirect carrying the
+/// authorization code arrives, returning the code and state parameters. Requests whose state
+/// doesn't match ours didn't come from our authorization request and are turned away.
+async fn wait_for_callback(
+    listener: TcpListener,
+    expected_state: &str,
+) -> Result<(String, String), String> {
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+
+        let mut buf = vec![0u8; 8192];
+        let n = stream.read(&mut buf).await.unwrap_or(0);
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let Some(target) = request
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("GET "))
+            .and_then(|line| line.split(' ').next())
+        else {
+            continue;
+        };
+        let Ok(callback) = url::Url::parse(&format!("http://127.0.0.1{target}")) else {
+            continue;
+        };
+        if callback.path() != "/callback" {
+            let _ = stream
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            continue;
+        }
+
+        let param = |name: &str| {
+            callback
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let state = param("state");
+        if state.as_deref() != Some(expected_state) {
+            let _ = stream
+                .write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            continue;
+        }
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}",
+            CALLBACK_PAGE.len(),
+            CALLBACK_PAGE
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+
+        if let Some(error) = param("error") {
+            return Err(format!("MCP authorization was denied: {error}"));
+        }
+        match (param("code"), state) {
+            (Some(code), Some(state)) => return Ok((code, state)),
+            _ => return Err("MCP authorization callback is missing the code".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    /// Sends a GET for `target` to the listener and returns the response's status line.
+    async fn get(port: u16, target: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let request = format!("GET {target} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response.lines().next().unwrap_or_default().to_string()
+    }
+
+    async fn listen() -> (TcpListener, u16) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        (listener, port)
+    }
+
+    #[tokio::test]
+    async fn callback_returns_code_and_state() {
+        let (listener, port) = listen().await;
+        let callback = tokio::spawn(async move { wait_for_callback(listener, "xyz").await });
+
+        let status = get(port, "/callback?code=abc%20def&state=xyz").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let (code, state) = callback.await.unwrap().unwrap();
+        assert_eq!((code.as_str(), state.as_str()), ("abc def", "xyz"));
+    }
+
+    #[tokio::test]
+    async fn callback_ignores_other_paths_and_mismatched_state() {
+        let (listener, port) = listen().await;
+        let callback = tokio::spawn(async move { wait_for_callback(listener, "xyz").await });
+
+        assert_eq!(get(port, "/favicon.ico").await, "HTTP/1.1 404 Not Found");
+        let status = get(port, "/callback?code=forged&state=other").await;
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        assert!(!callback.is_finished());
+
+        get(port, "/callback?code=abc&state=xyz").await;
+        let (code, _) = callback.await.unwrap().unwrap();
+        assert_eq!(code, "abc");
+    }
+
+    #[tokio::test]
+    async fn callback_reports_denied_authorization() {
+        let (listener, port) = listen().await;
+        let callback = tokio::spawn(async move { wait_for_callback(listener, "xyz").await });
+
+        get(port, "/callback?error=access_denied&state=xyz").await;
+        let error = callback.await.unwrap().unwrap_err();
+        assert!(error.contains("access_denied"), "{error}");
+    }
+}
@@ -0,0 +1,295 @@
+use rmcp::model::{CallToolResult, RawContent, RawEmbeddedResource, ResourceContents, Tool};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::prompts::{resource_attachment, Attachment};
+use super::schema;
+
+/// A tool result split by content type, so binary content never reaches the model as text.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolOutput {
+    /// What the model sees: text blocks, plus a placeholder for everything split out below.
+    pub text: String,
+    /// Images, audio and binary resources, as data URIs.
+    pub attachments: Vec<Attachment>,
+    /// Embedded text resources, meant to be kept as artifacts at the path they're named by
+    /// (see [`artifact_path`]).
+    pub resources: Vec<Attachment>,
+    /// The result's `structuredContent`, left out when it doesn't match the tool's
+    /// `outputSchema`.
+    pub structured_content: Option<Value>,
+    /// Why the structured content was rejected, one line per violation.
+    pub schema_violations: Vec<String>,
+    pub is_error: bool,
+}
+
+/// Where a server's text resource is kept as an artifact: under `mcp/<server id>/`, so it
+/// can't land on one of the user's own, followed by the URI without its scheme or any empty,
+/// `.` and `..` segments.
+pub fn artifact_path(server_id: &str, uri: &str) -> String {
+    let path = uri.split_once("://").map_or(uri, |(_, path)| path);
+    let segments: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+        .collect();
+    if segments.is_empty() {
+        format!("mcp/{server_id}/resource")
+    } else {
+        format!("mcp/{server_id}/{}", segments.join("/"))
+    }
+}
+
+impl ToolOutput {
+    /// Splits a result from `server_id`, checking its structured content against `tool`'s
+    /// output schema. Error results are exempt, as the spec only constrains successful ones.
+    pub fn new(result: CallToolResult, server_id: &str, tool: Option<&Tool>) -> Self {
+        let is_error = result.is_error.unwrap_or(false);
+        let mut structured_content = result.structured_content;
+        let mut schema_violations = Vec::new();
+        if let Some(tool) = tool.filter(|_| !is_error) {
+            if let Err(violations) =
+                schema::check_structured_content(tool, structured_content.as_ref())
+            {
+                structured_content = None;
+                schema_violations = violations;
+            }
+        }
+
+        let mut text = Vec::new();
+        let mut attachments = Vec::new();
+        let mut resources = Vec::new();
+
+        for content in result.content {
+            match content.raw {
+                RawContent::Text(content) => text.push(content.text),
+                RawContent::Image(image) => {
+                    text.push(format!("[Image attached: {}]", image.mime_type));
+                    attachments.push(Attachment {
+                        name: "image".to_string(),
+                        content: format!("data:{};base64,{}", image.mime_type, image.data),
+                        mime_type: image.mime_type,
+                    });
+                }
+                RawContent::Audio(audio) => {
+                    text.push(format!("[Audio attached: {}]", audio.mime_type));
+                    attachments.push(Attachment {
+                        name: "audio".to_string(),
+                        content: format!("data:{};base64,{}", audio.mime_type, audio.data),
+                        mime_type: audio.mime_type,
+                    });
+                }
+                RawContent::Resource(RawEmbeddedResource { resource, .. }) => {
+                    let is_text = matches!(resource, ResourceContents::TextResourceContents { .. });
+                    let mut attachment = resource_attachment(resource);
+                    if is_text {
+                        attachment.name = artifact_path(server_id, &attachment.name);
+                        text.push(format!("[Resource saved as artifact: {}]", attachment.name));
+                        resources.push(attachment);
+                    } else {
+                        text.push(format!(
+                            "[Resource attached: {} ({})]",
+                            attachment.name, attachment.mime_type
+                        ));
+                        attachments.push(attachment);
+                    }
+                }
+                RawContent::ResourceLink(link) => {
+                    text.push(format!("[{}]({})", link.name, link.uri));
+                }
+            }
+        }
+
+        // Structured-only results still need something for the model to read, even if it's
+        // only why the structured content was dropped.
+        if text.is_empty() {
+            if let Some(structured) = &structured_content {
+                text.push(structured.to_string());
+            } else if !schema_violations.is_empty() {
+                text.push(format!(
+                    "The tool's structured output was rejected because it doesn't match the \
+                     tool's output schema:\n{}",
+                    schema_violations.join("\n")
+                ));
+            }
+        }
+
+        ToolOutput {
+            text: text.join("\n"),
+            attachments,
+            resources,
+            structured_content,
+            schema_violations,
+            is_error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result(value: Value) -> CallToolResult {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn splits_content_by_type() {
+        let output = ToolOutput::new(
+            result(json!({
+                "content": [
+                    { "type": "text", "text": "Here you go" },
+                    { "type": "image", "data": "aW1n", "mimeType": "image/png" },
+                    { "type": "audio", "data": "YXVk", "mimeType": "audio/wav" },
+                    { "type": "resource_link", "uri": "https://example.com/a", "name": "a" }
+                ]
+            })),
+            "srv",
+            None,
+        );
+
+        assert_eq!(
+            output.text,
+            "Here you go\n[Image attached: image/png]\n[Audio attached: audio/wav]\n[a](https://example.com/a)"
+        );
+        let contents: Vec<_> = output.attachments.iter().map(|a| &a.content).collect();
+        assert_eq!(
+            contents,
+            ["data:image/png;base64,aW1n", "data:audio/wav;base64,YXVk"]
+        );
+        assert!(output.resources.is_empty());
+        assert!(!output.is_error);
+    }
+
+    #[test]
+    fn text_resources_become_artifacts_under_the_server() {
+        let output = ToolOutput::new(
+            result(json!({
+                "content": [{
+                    "type": "resource",
+                    "resource": { "uri": "file:///tmp/../notes.md", "mimeType": "text/markdown", "text": "# Notes" }
+                }]
+            })),
+            "srv",
+            None,
+        );
+
+        assert_eq!(
+            output.text,
+            "[Resource saved as artifact: mcp/srv/tmp/notes.md]"
+        );
+        assert!(output.attachments.is_empty());
+        assert_eq!(output.resources.len(), 1);
+        assert_eq!(output.resources[0].name, "mcp/srv/tmp/notes.md");
+        assert_eq!(output.resources[0].mime_type, "text/markdown");
+        assert_eq!(output.resources[0].content, "# Notes");
+    }
+
+    #[test]
+    fn binary_resources_are_attached() {
+        let output = ToolOutput::new(
+            result(json!({
+                "content": [{
+                    "type": "resource",
+                    "resource": { "uri": "file:///report.pdf", "mimeType": "application/pdf", "blob": "cGRm" }
+                }]
+            })),
+            "srv",
+            None,
+        );
+
+        assert_eq!(
+            output.text,
+            "[Resource attached: file:///report.pdf (application/pdf)]"
+        );
+        assert!(output.resources.is_empty());
+        assert_eq!(output.attachments.len(), 1);
+        assert_eq!(output.attachments[0].name, "file:///report.pdf");
+        assert_eq!(
+            output.attachments[0].content,
+            "data:application/pdf;base64,cGRm"
+        );
+    }
+
+    #[test]
+    fn artifact_paths_stay_inside_the_server_directory() {
+        assert_eq!(artifact_path("srv", "memo://insights"), "mcp/srv/insights");
+        assert_eq!(
+            artifact_path("srv", "../../etc/passwd"),
+            "mcp/srv/etc/passwd"
+        );
+        assert_eq!(artifact_path("srv", "a\\..\\b"), "mcp/srv/a/b");
+        assert_eq!(artifact_path("srv", "file:///"), "mcp/srv/resource");
+    }
+
+    fn tool_with_output_schema() -> Tool {
+        let mut tool = Tool::new("weather", "Current weather", rmcp::model::JsonObject::new());
+        let schema = json!({
+            "type": "object",
+            "properties": { "celsius": { "type": "number" } },
+            "required": ["celsius"]
+        });
+        tool.output_schema = Some(std::sync::Arc::new(schema.as_object().unwrap().clone()));
+        tool
+    }
+
+    #[test]
+    fn keeps_structured_content_matching_the_schema() {
+        let tool = tool_with_output_schema();
+        let output = ToolOutput::new(
+            result(json!({ "content": [], "structuredContent": { "celsius": 21 } })),
+            "srv",
+            Some(&tool),
+        );
+
+        assert_eq!(output.structured_content, Some(json!({ "celsius": 21 })));
+        assert!(output.schema_violations.is_empty());
+        assert_eq!(output.text, r#"{"celsius":21}"#);
+    }
+
+    #[test]
+    fn drops_structured_content_violating_the_schema() {
+        let tool = tool_with_output_schema();
+        let output = ToolOutput::new(
+            result(json!({ "content": [], "structuredContent": { "celsius": "warm" } })),
+            "srv",
+            Some(&tool),
+        );
+
+        assert_eq!(output.structured_content, None);
+        assert_eq!(output.schema_violations.len(), 1);
+        // With no text blocks, the model is told why instead of getting nothing
+        assert!(output.text.contains("output schema"), "{}", output.text);
+        assert!(output.text.contains(&output.schema_violations[0]));
+
+        // Text blocks are left as they are
+        let output = ToolOutput::new(
+            result(json!({
+                "content": [{ "type": "text", "text": "Warm" }],
+                "structuredContent": { "celsius": "warm" }
+            })),
+            "srv",
+            Some(&tool),
+        );
+        assert_eq!(output.structured_content, None);
+        assert_eq!(output.text, "Warm");
+    }
+
+    #[test]
+    fn error_results_are_not_checked_against_the_schema() {
+        let tool = tool_with_output_schema();
+        let output = ToolOutput::new(
+            result(json!({
+                "content": [{ "type": "text", "text": "Station offline" }],
+                "isError": true
+            })),
+            "srv",
+            Some(&tool),
+        );
+
+        assert!(output.is_error);
+        assert!(output.schema_violations.is_empty());
+        assert_eq!(output.text, "Station offline");
+    }
+}
@@ -0,0 +1,276 @@
+use globset::Glob;
+use rmcp::model::{CallToolResult, Content, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::approval::PendingReplies;
+
+pub const TOOL_APPROVAL_EVENT: &str = "mcp://tool-approval";
+
+/// How long an "ask" call may wait for the user before it is treated as denied.
+pub const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Rules under this key apply to every project, after the project's own rules.
+const GLOBAL_RULES: &str = "";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Allow,
+    Deny,
+    Ask,
+}
+
+/// A single policy rule. Every condition that is set must match for the rule to apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRule {
+    /// Server id, or any server when unset.
+    pub server: Option<String>,
+    /// Glob over the tool name, e.g. `write_*`.
+    pub tool: Option<String>,
+    /// Matches the tool's `destructiveHint` annotation, which defaults to true.
+    pub destructive: Option<bool>,
+    /// Matches the tool's `readOnlyHint` annotation, which defaults to false.
+    pub read_only: Option<bool>,
+    pub action: PolicyAction,
+}
+
+impl PolicyRule {
+    fn matches(&self, server_id: &str, tool_name: &str, tool: Option<&Tool>) -> bool {
+        if self
+            .server
+            .as_deref()
+            .is_some_and(|server| server != server_id)
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.tool {
+            // A rule we can't parse never matches rather than matching everything.
+            match Glob::new(pattern) {
+                Ok(glob) if glob.compile_matcher().is_match(tool_name) => {}
+                _ => return false,
+            }
+        }
+
+        // Missing hints take the spec's defaults, so a tool without annotations, or one we
+        // couldn't look up, counts as destructive. Read-only tools can't be destructive.
+        let annotations = tool.and_then(|tool| tool.annotations.as_ref());
+        let read_only = annotations.and_then(|a| a.read_only_hint).unwrap_or(false);
+        let destructive =
+            !read_only && annotations.and_then(|a| a.destructive_hint).unwrap_or(true);
+        self.destructive
+            .is_none_or(|expected| destructive == expected)
+            && self.read_only.is_none_or(|expected| read_only == expected)
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolApprovalEvent<'a> {
+    pub request_id: &'a str,
+    pub server_id: &'a str,
+    pub tool: &'a str,
+    pub arguments: &'a Value,
+    pub session_id: Option<&'a str>,
+}
+
+/// Per-project tool rules, persisted as JSON in the app data dir.
+#[derive(Default)]
+pub struct PolicyState {
+    rules: RwLock<Option<HashMap<String, Vec<PolicyRule>>>>,
+    pub approvals: PendingReplies<bool>,
+}
+
+impl PolicyState {
+    /// Picks the action for a call: the project's rules first, then the global ones, with
+    /// the first matching rule winning. Calls no rule covers are allowed.
+    pub fn evaluate(
+        &self,
+        app: &AppHandle,
+        project_id: Option<&str>,
+        server_id: &str,
+        tool_name: &str,
+        tool: Option<&Tool>,
+    ) -> Result<PolicyAction, String> {
+        self.load(app)?;
+        let rules = self.rules.read().unwrap();
+        let rules = rules.as_ref().expect("policy rules are loaded");
+
+        let project = project_id.and_then(|id| rules.get(id));
+        Ok(project
+            .into_iter()
+            .chain(rules.get(GLOBAL_RULES))
+            .flatten()
+            .find(|rule| rule.matches(server_id, tool_name, tool))
+            .map_or(PolicyAction::Allow, |rule| rule.action))
+    }
+
+    pub fn get(
+        &self,
+        app: &AppHandle,
+        project_id: Option<&str>,
+    ) -> Result<Vec<PolicyRule>, String> {
+        self.load(app)?;
+        let rules = self.rules.read().unwrap();
+        let rules = rules.as_ref().expect("policy rules are loaded");
+        Ok(rules
+            .get(project_id.unwrap_or(GLOBAL_RULES))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    pub fn set(
+        &self,
+        app: &AppHandle,
+        project_id: Option<&str>,
+        project_rules: Vec<PolicyRule>,
+    ) -> Result<(), String> {
+        for rule in &project_rules {
+            if let Some(pattern) = &rule.tool {
+                Glob::new(pattern).map_err(|e| format!("Invalid tool pattern `{pattern}`: {e}"))?;
+            }
+        }
+
+        self.load(app)?;
+        let mut rules = self.rules.write().unwrap();
+        let rules = rules.as_mut().expect("policy rules are loaded");
+        let key = project_id.unwrap_or(GLOBAL_RULES).to_string();
+        if project_rules.is_empty() {
+            rules.remove(&key);
+        } else {
+            rules.insert(key, project_rules);
+        }
+
+        let path = policy_path(app)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_vec_pretty(&*rules).map_err(|e| e.to_string())?;
+        write_atomically(&path, &data)
+    }
+
+    /// Reads the rules file the first time the rules are needed. Only a missing file means
+    /// there are no rules: a file we can't read is an error rather than "allow everything",
+    /// and is retried on the next call instead of being overwritten.
+    fn load(&self, app: &AppHandle) -> Result<(), String> {
+        if self.rules.read().unwrap().is_some() {
+            return Ok(());
+        }
+        let path = policy_path(app)?;
+        let loaded = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|e| format!("Invalid tool policy file {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(format!(
+                    "Could not read tool policy file {}: {e}",
+                    path.display()
+                ))
+            }
+        };
+        self.rules.write().unwrap().get_or_insert(loaded);
+        Ok(())
+    }
+}
+
+/// Writes a temporary file next to `path` and renames it over the original, so a crash
+/// mid-write leaves the previous rules in place instead of a truncated file that `load`
+/// would refuse.
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
+fn policy_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("tool-policies.json"))
+}
+
+/// The result handed back to the model in place of a call the policy stopped.
+pub fn denied(tool_name: &str, reason: &str) -> CallToolResult {
+    CallToolResult::error(vec![Content::text(format!(
+        "Tool `{tool_name}` was not run: {reason}. Do not retry this call unless the user asks."
+    ))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{JsonObject, ToolAnnotations};
+    use serde_json::json;
+
+    fn rule(value: Value) -> PolicyRule {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn tool(read_only: Option<bool>, destructive: Option<bool>) -> Tool {
+        let mut annotations = ToolAnnotations::new();
+        annotations.read_only_hint = read_only;
+        annotations.destructive_hint = destructive;
+        Tool::new("write_file", "", JsonObject::new()).annotate(annotations)
+    }
+
+    #[test]
+    fn server_and_tool_pattern_must_match() {
+        let rule = rule(json!({ "server": "fs", "tool": "write_*", "action": "ask" }));
+        assert!(rule.matches("fs", "write_file", None));
+        assert!(!rule.matches("fs", "read_file", None));
+        assert!(!rule.matches("git", "write_file", None));
+    }
+
+    #[test]
+    fn invalid_patterns_never_match() {
+        let rule = rule(json!({ "tool": "[", "action": "deny" }));
+        assert!(!rule.matches("fs", "[", None));
+    }
+
+    #[test]
+    fn missing_hints_take_the_spec_defaults() {
+        let destructive = rule(json!({ "destructive": true, "action": "ask" }));
+        let read_only = rule(json!({ "readOnly": true, "action": "allow" }));
+
+        // No annotations at all, or annotations without the hints.
+        let unannotated = Tool::new("write_file", "", JsonObject::new());
+        for tool in [&unannotated, &tool(None, None)] {
+            assert!(destructive.matches("fs", "write_file", Some(tool)));
+            assert!(!read_only.matches("fs", "write_file", Some(tool)));
+        }
+
+        assert!(!destructive.matches("fs", "write_file", Some(&tool(None, Some(false)))));
+        assert!(!destructive.matches("fs", "read_file", Some(&tool(Some(true), None))));
+        assert!(read_only.matches("fs", "read_file", Some(&tool(Some(true), None))));
+    }
+
+    #[test]
+    fn unknown_tools_count_as_destructive() {
+        let destructive = rule(json!({ "destructive": true, "action": "ask" }));
+        let read_only = rule(json!({ "readOnly": true, "action": "allow" }));
+        assert!(destructive.matches("fs", "write_file", None));
+        assert!(!read_only.matches("fs", "write_file", None));
+    }
+
+    #[test]
+    fn writes_replace_the_file_without_leftovers() {
+        let dir = std::env::temp_dir().join(format!("c-chat-policy-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tool-policies.json");
+
+        write_atomically(&path, b"{\"old\": []}").unwrap();
+        write_atomically(&path, b"{}").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"{}");
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
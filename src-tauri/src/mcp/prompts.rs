@@ -0,0 +1,108 @@
+use rmcp::model::{GetPromptResult, PromptMessage, PromptMessageContent, PromptMessageRole};
+use rmcp::model::{RawEmbeddedResource, ResourceContents};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Mirrors the frontend `Attachment` so rendered prompts can be stored as-is.
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+    /// Text content, or a base64 data URI for binary content.
+    pub content: String,
+}
+
+/// Mirrors the frontend chat `Message`, ready for `addMessage`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub role: &'static str,
+    pub content: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedPrompt {
+    pub description: Option<String>,
+    pub messages: Vec<ChatMessage>,
+}
+
+impl From<GetPromptResult> for RenderedPrompt {
+    fn from(result: GetPromptResult) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        RenderedPrompt {
+            description: result.description,
+            messages: result
+                .messages
+                .into_iter()
+                .map(|message| render_message(message, timestamp))
+                .collect(),
+        }
+    }
+}
+
+fn render_message(message: PromptMessage, timestamp: u64) -> ChatMessage {
+    let role = match message.role {
+        PromptMessageRole::User => "user",
+        PromptMessageRole::Assistant => "assistant",
+    };
+
+    let mut content = String::new();
+    let mut attachments = Vec::new();
+    match message.content {
+        PromptMessageContent::Text { text } => content = text,
+        PromptMessageContent::Image { image } => attachments.push(Attachment {
+            name: "image".to_string(),
+            content: format!("data:{};base64,{}", image.mime_type, image.data),
+            mime_type: image.raw.mime_type,
+        }),
+        PromptMessageContent::Resource { resource } => {
+            let RawEmbeddedResource { resource, .. } = resource.raw;
+            attachments.push(resource_attachment(resource));
+        }
+        PromptMessageContent::ResourceLink { link } => {
+            content = format!("[{}]({})", link.name, link.uri);
+        }
+    }
+
+    ChatMessage {
+        role,
+        content,
+        timestamp,
+        attachments,
+    }
+}
+
+pub fn resource_attachment(resource: ResourceContents) -> Attachment {
+    match resource {
+        ResourceContents::TextResourceContents {
+            uri,
+            mime_type,
+            text,
+            ..
+        } => Attachment {
+            name: uri,
+            mime_type: mime_type.unwrap_or_else(|| "text/plain".to_string()),
+            content: text,
+        },
+        ResourceContents::BlobResourceContents {
+            uri,
+            mime_type,
+            blob,
+            ..
+        } => {
+            let mime_type = mime_type.unwrap_or_else(|| "application/octet-stream".to_string());
+            Attachment {
+                name: uri,
+                content: format!("data:{mime_type};base64,{blob}"),
+                mime_type,
+            }
+        }
+    }
+}
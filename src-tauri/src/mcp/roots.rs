@@ -0,0 +1,54 @@
+use rmcp::model::Root;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use url::Url;
+
+/// A directory the user has exposed to a server, as sent from the frontend.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RootDirectory {
+    /// Absolute filesystem path or `file://` URI.
+    pub path: String,
+    pub name: Option<String>,
+}
+
+/// Per-server roots reported to servers through `roots/list`.
+#[derive(Default)]
+pub struct RootsState {
+    roots: RwLock<HashMap<String, Vec<Root>>>,
+}
+
+impl RootsState {
+    pub fn get(&self, server_id: &str) -> Vec<Root> {
+        let roots = self.roots.read().unwrap();
+        roots.get(server_id).cloned().unwrap_or_default()
+    }
+
+    /// Replaces the roots for a server, returning whether they changed.
+    pub fn set(&self, server_id: &str, directories: Vec<RootDirectory>) -> Result<bool, String> {
+        let updated = directories
+            .into_iter()
+            .map(|directory| {
+                Ok(Root {
+                    uri: to_uri(&directory.path)?,
+                    name: directory.name,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut roots = self.roots.write().unwrap();
+        let previous = roots.insert(server_id.to_string(), updated.clone());
+        Ok(previous.unwrap_or_default() != updated)
+    }
+}
+
+fn to_uri(path: &str) -> Result<String, String> {
+    if path.starts_with("file://") {
+        return Url::parse(path)
+            .map(String::from)
+            .map_err(|e| format!("Invalid root URI {path}: {e}"));
+    }
+    Url::from_directory_path(path)
+        .map(String::from)
+        .map_err(|_| format!("Root must be an absolute directory path: {path}"))
+}
@@ -0,0 +1,113 @@
+use rmcp::model::{
+    Content, CreateMessageRequestParam, CreateMessageResult, RawContent, Role, SamplingMessage,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use super::approval::PendingReplies;
+
+pub const SAMPLING_REQUEST_EVENT: &str = "mcp://sampling-request";
+
+/// How long a sampling request may wait for the user before it is rejected.
+pub const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// OpenAI-compatible endpoint and model that serve `sampling/createMessage` requests.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+}
+
+#[derive(Default)]
+pub struct SamplingState {
+    pub config: RwLock<Option<SamplingConfig>>,
+    pub approvals: PendingReplies<bool>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingRequestEvent<'a> {
+    pub request_id: &'a str,
+    pub server_id: &'a str,
+    pub model: &'a str,
+    pub system_prompt: Option<&'a str>,
+    pub messages: &'a [SamplingMessage],
+    pub max_tokens: u32,
+}
+
+/// Runs the request against the configured chat completions endpoint.
+pub async fn complete(
+    config: &SamplingConfig,
+    params: &CreateMessageRequestParam,
+) -> Result<CreateMessageResult, String> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = &params.system_prompt {
+        messages.push(json!({ "role": "system", "content": system_prompt }));
+    }
+    for message in &params.messages {
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        let content = match &message.content.raw {
+            RawContent::Text(text) => json!(text.text),
+            RawContent::Image(image) => json!([{
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", image.mime_type, image.data) }
+            }]),
+            _ => return Err("Unsupported content type in sampling request".to_string()),
+        };
+        messages.push(json!({ "role": role, "content": content }));
+    }
+
+    let mut body = json!({
+        "model": config.model,
+        "messages": messages,
+        "max_tokens": params.max_tokens,
+        "stream": false,
+    });
+    if let Some(temperature) = params.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(stop) = &params.stop_sequences {
+        body["stop"] = json!(stop);
+    }
+
+    let url = format!("{}/chat/completions", config.url.trim_end_matches('/'));
+    let mut request = reqwest::Client::new().post(url).json(&body);
+    if let Some(api_key) = config.api_key.as_deref().filter(|key| !key.is_empty()) {
+        request = request.bearer_auth(api_key);
+    }
+    let completion: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let choice = &completion["choices"][0];
+    let text = choice["message"]["content"].as_str().unwrap_or_default();
+    let stop_reason = match choice["finish_reason"].as_str() {
+        Some("length") => Some(CreateMessageResult::STOP_REASON_END_MAX_TOKEN.to_string()),
+        Some("stop") => Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
+        other => other.map(str::to_string),
+    };
+
+    Ok(CreateMessageResult {
+        model: completion["model"]
+            .as_str()
+            .unwrap_or(&config.model)
+            .to_string(),
+        stop_reason,
+        message: SamplingMessage {
+            role: Role::Assistant,
+            content: Content::text(text),
+        },
+    })
+}
@@ -0,0 +1,128 @@
+use rmcp::model::{CallToolResult, Content, JsonObject, Tool};
+use serde_json::Value;
+
+/// Checks model-produced arguments against a tool's `inputSchema`, returning one readable
+/// line per violation. Schemas we can't compile are left for the server to enforce.
+pub fn check_arguments(tool: &Tool, arguments: &Value) -> Result<(), Vec<String>> {
+    check_object(arguments)?;
+    validate(tool.input_schema.as_ref(), arguments, "arguments")
+}
+
+/// Tool arguments are always an object, whatever the tool's schema says.
+pub fn check_object(arguments: &Value) -> Result<(), Vec<String>> {
+    if arguments.is_object() {
+        Ok(())
+    } else {
+        Err(vec!["arguments: must be a JSON object".to_string()])
+    }
+}
+
+/// Checks a result's `structuredContent` against the tool's `outputSchema`. Tools that declare
+/// a schema must return structured content, so a missing value is a violation too.
+pub fn check_structured_content(tool: &Tool, content: Option<&Value>) -> Result<(), Vec<String>> {
+    let Some(schema) = &tool.output_schema else {
+        return Ok(());
+    };
+    match content {
+        Some(content) => validate(schema, content, "structuredContent"),
+        None => Err(vec![
+            "structuredContent: missing, but the tool declares an output schema".to_string(),
+        ]),
+    }
+}
+
+/// Validates `instance` against `schema`, prefixing each violation with `label` and the
+/// instance path.
+pub(crate) fn validate(
+    schema: &JsonObject,
+    instance: &Value,
+    label: &str,
+) -> Result<(), Vec<String>> {
+    let schema = Value::Object(schema.clone());
+    let Ok(validator) = jsonschema::validator_for(&schema) else {
+        return Ok(());
+    };
+
+    let violations: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|error| {
+            let path = error.instance_path.as_str();
+            if path.is_empty() {
+                format!("{label}: {error}")
+            } else {
+                format!("{label}{path}: {error}")
+            }
+        })
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Builds the error result handed back to the model instead of dispatching a bad call.
+pub fn invalid_arguments(tool_name: &str, violations: &[String]) -> CallToolResult {
+    let mut text = format!(
+        "Tool `{tool_name}` was not called because its arguments do not match the input schema:\n"
+    );
+    for violation in violations {
+        text.push_str("- ");
+        text.push_str(violation);
+        text.push('\n');
+    }
+    text.push_str("Correct the arguments and call the tool again.");
+    CallToolResult::error(vec![Content::text(text)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(input_schema: Value) -> Tool {
+        let Value::Object(input_schema) = input_schema else {
+            unreachable!()
+        };
+        Tool::new("search", "Search", input_schema)
+    }
+
+    #[test]
+    fn arguments_must_be_an_object() {
+        let search = tool(json!({ "type": "object" }));
+        assert!(check_arguments(&search, &json!({})).is_ok());
+        for arguments in [json!("query"), json!([1]), json!(null), json!(3)] {
+            assert_eq!(
+                check_arguments(&search, &arguments).unwrap_err(),
+                vec!["arguments: must be a JSON object"]
+            );
+        }
+    }
+
+    #[test]
+    fn arguments_are_checked_against_the_input_schema() {
+        let search = tool(json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer", "maximum": 50 }
+            },
+            "required": ["query"]
+        }));
+        assert!(check_arguments(&search, &json!({ "query": "rust", "limit": 10 })).is_ok());
+
+        let violations = check_arguments(&search, &json!({ "limit": 100 })).unwrap_err();
+        assert_eq!(violations.len(), 2, "{violations:?}");
+        assert!(violations
+            .iter()
+            .any(|v| v.starts_with("arguments/limit: ")));
+        assert!(violations.iter().any(|v| v.contains("\"query\"")));
+    }
+
+    #[test]
+    fn invalid_schemas_are_left_to_the_server() {
+        let search = tool(json!({ "type": "not-a-type" }));
+        assert!(check_arguments(&search, &json!({ "anything": true })).is_ok());
+    }
+}
@@ -0,0 +1,47 @@
+/// Keychain service the MCP server secrets are stored under (Keychain, Credential Manager or
+/// the Secret Service, depending on the platform).
+const SERVICE: &str = "c-chat-mcp";
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Secret name is required".to_string());
+    }
+    keyring::Entry::new(SERVICE, name).map_err(|e| e.to_string())
+}
+
+pub fn get(name: &str) -> Result<String, String> {
+    find(name)?.ok_or_else(|| format!("Secret `{name}` is not set"))
+}
+
+/// Reads several secrets in one go off the async runtime, since the platform stores can block
+/// (on an unlock prompt, say). Fails on the first one that isn't set.
+pub async fn get_all(names: Vec<String>) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || names.iter().map(|name| get(name)).collect())
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Like [`get`], but a secret that isn't set is `None` rather than an error.
+pub fn find(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Could not read secret `{name}`: {e}")),
+    }
+}
+
+pub fn set(name: &str, value: &str) -> Result<(), String> {
+    entry(name)?.set_password(value).map_err(|e| e.to_string())
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn exists(name: &str) -> bool {
+    entry(name).is_ok_and(|entry| entry.get_password().is_ok())
+}
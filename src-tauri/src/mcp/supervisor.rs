@@ -0,0 +1,148 @@
+use rmcp::model::ClientRequest;
+use rmcp::service::PeerRequestOptions;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task::AbortHandle;
+
+use super::catalog::CatalogState;
+use super::{connect, shutdown, McpClient, McpState, ServerConfig};
+
+pub const STATUS_EVENT: &str = "mcp://status";
+
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Failed attempts before the server is reported as failed. We keep retrying at
+/// `MAX_BACKOFF` after that, so a server that comes back is picked up without a restart.
+const ATTEMPTS_BEFORE_FAILED: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Connected,
+    Reconnecting,
+    Failed,
+    Disconnected,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusEvent<'a> {
+    id: &'a str,
+    status: Status,
+    attempt: u32,
+    error: Option<String>,
+}
+
+pub fn emit(app: &AppHandle, id: &str, status: Status, attempt: u32, error: Option<String>) {
+    let event = StatusEvent {
+        id,
+        status,
+        attempt,
+        error,
+    };
+    let _ = app.emit(STATUS_EVENT, event);
+}
+
+/// Starts the health check loop for the client registered under `id`.
+pub fn spawn(app: AppHandle, id: String) -> AbortHandle {
+    tokio::spawn(supervise(app, id)).abort_handle()
+}
+
+async fn supervise(app: AppHandle, id: String) {
+    loop {
+        tokio::time::sleep(PING_INTERVAL).await;
+
+        let client = {
+            let state = app.state::<McpState>();
+            let clients = state.clients.lock().await;
+            match clients.get(&id) {
+                Some(connection) => connection.client.clone(),
+                None => return,
+            }
+        };
+
+        if let Err(error) = ping(&client).await {
+            drop(client);
+            if !reconnect(&app, &id, error).await {
+                return;
+            }
+        }
+    }
+}
+
+async fn ping(client: &McpClient) -> Result<(), String> {
+    if client.is_transport_closed() {
+        return Err("Transport closed".to_string());
+    }
+
+    let options = PeerRequestOptions {
+        timeout: Some(PING_TIMEOUT),
+        meta: None,
+    };
+    client
+        .send_request_with_option(ClientRequest::PingRequest(Default::default()), options)
+        .await
+        .map_err(|e| e.to_string())?
+        .await_response()
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Re-establishes the session with exponential backoff, swapping the new client into
+/// the state. Keeps trying until it succeeds; returns `false` if the server was removed.
+async fn reconnect(app: &AppHandle, id: &str, mut error: String) -> bool {
+    let state = app.state::<McpState>();
+    let Some(config) = set_status(&state, id, Status::Reconnecting).await else {
+        return false;
+    };
+
+    let mut delay = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        if attempt <= ATTEMPTS_BEFORE_FAILED {
+            emit(app, id, Status::Reconnecting, attempt, Some(error.clone()));
+        }
+
+        match connect(app, id, &config).await {
+            Ok((client, transport)) => {
+                let mut clients = state.clients.lock().await;
+                // Disconnected while we were reconnecting; dropping the new client closes it.
+                let Some(connection) = clients.get_mut(id) else {
+                    return false;
+                };
+                let stale = std::mem::replace(&mut connection.client, Arc::new(client));
+                connection.status = Status::Connected;
+                connection.transport = transport;
+                drop(clients);
+                app.state::<CatalogState>().invalidate(id);
+
+                shutdown(stale).await;
+                emit(app, id, Status::Connected, attempt, None);
+                return true;
+            }
+            Err(e) => error = e.to_string(),
+        }
+
+        if attempt == ATTEMPTS_BEFORE_FAILED {
+            if set_status(&state, id, Status::Failed).await.is_none() {
+                return false;
+            }
+            emit(app, id, Status::Failed, attempt, Some(error.clone()));
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_BACKOFF);
+    }
+}
+
+async fn set_status(state: &McpState, id: &str, status: Status) -> Option<ServerConfig> {
+    let mut clients = state.clients.lock().await;
+    let connection = clients.get_mut(id)?;
+    connection.status = status;
+    Some(connection.config.clone())
+}
@@ -0,0 +1,252 @@
+use futures::{SinkExt, StreamExt};
+use reqwest::header::HeaderMap;
+use rmcp::service::{RoleClient, RxJsonRpcMessage, TxJsonRpcMessage};
+use rmcp::transport::Transport;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::SEC_WEBSOCKET_PROTOCOL;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+/// How often we ping the server to keep idle gateways from dropping the socket.
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+/// With pings going out, silence for this long means the connection is dead.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Carries JSON-RPC messages over a ws/wss connection, one message per text frame.
+pub struct WebSocketTransport {
+    outgoing: mpsc::Sender<Message>,
+    incoming: mpsc::Receiver<RxJsonRpcMessage<RoleClient>>,
+    reader: AbortHandle,
+}
+
+impl WebSocketTransport {
+    pub async fn connect(url: &str, headers: HeaderMap) -> Result<Self, io::Error> {
+        let mut request = url.into_client_request().map_err(io::Error::other)?;
+        request.headers_mut().extend(headers);
+        // MCP's WebSocket binding asks for the `mcp` subprotocol, and the server has to agree.
+        request
+            .headers_mut()
+            .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("mcp"));
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(io::Error::other)?;
+        let (mut sink, mut stream) = socket.split();
+
+        let (outgoing, mut outgoing_rx) = mpsc::channel::<Message>(64);
+        // Runs until every sender is gone, so dropping the transport also stops the pings.
+        tokio::spawn(async move {
+            let mut ping = tokio::time::interval(PING_INTERVAL);
+            ping.tick().await;
+            loop {
+                let message = tokio::select! {
+                    message = outgoing_rx.recv() => match message {
+                        Some(message) => message,
+                        None => break,
+                    },
+                    _ = ping.tick() => Message::Ping(Default::default()),
+                };
+                let closing = matches!(message, Message::Close(_));
+                if sink.send(message).await.is_err() || closing {
+                    break;
+                }
+            }
+        });
+
+        let (incoming_tx, incoming) = mpsc::channel(64);
+        let reader = tokio::spawn(async move {
+            // Pongs and the server's own pings count as activity; tungstenite answers pings.
+            while let Ok(Some(Ok(frame))) = tokio::time::timeout(READ_TIMEOUT, stream.next()).await
+            {
+                let message = match frame {
+                    Message::Text(text) => serde_json::from_str(text.as_str()),
+                    Message::Binary(data) => serde_json::from_slice(&data),
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+                // Frames that aren't JSON-RPC are skipped rather than killing the session.
+                if let Ok(message) = message {
+                    if incoming_tx.send(message).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        })
+        .abort_handle();
+
+        Ok(Self {
+            outgoing,
+            incoming,
+            reader,
+        })
+    }
+}
+
+impl Transport<RoleClient> for WebSocketTransport {
+    type Error = io::Error;
+
+    fn name() -> std::borrow::Cow<'static, str> {
+        "WebSocketTransport".into()
+    }
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let outgoing = self.outgoing.clone();
+        async move {
+            let text = serde_json::to_string(&item).map_err(io::Error::other)?;
+            outgoing
+                .send(Message::text(text))
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "WebSocket closed"))
+        }
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<RoleClient>>> + Send {
+        self.incoming.recv()
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.reader.abort();
+        // The writer exits once the close frame is out.
+        let _ = self.outgoing.send(Message::Close(None)).await;
+        Ok(())
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{
+        ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
+    };
+    use rmcp::service::{NotificationContext, RequestContext, RoleServer};
+    use rmcp::{ClientHandler, ErrorData, ServerHandler, ServiceExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+    use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+    struct EchoServer;
+
+    impl ServerHandler for EchoServer {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo {
+                capabilities: ServerCapabilities::builder().enable_tools().build(),
+                ..Default::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<PaginatedRequestParam>,
+            context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, ErrorData> {
+            // Also send something unprompted, to see it reach the client
+            let _ = context.peer.notify_tool_list_changed().await;
+            let tool = Tool::new("echo", "Echoes its input", rmcp::model::JsonObject::new());
+            Ok(ListToolsResult::with_all_items(vec![tool]))
+        }
+    }
+
+    /// Counts the tool list change notifications it gets.
+    struct Notified(mpsc::UnboundedSender<()>);
+
+    impl ClientHandler for Notified {
+        async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+            let _ = self.0.send(());
+        }
+    }
+
+    /// Agrees to the `mcp` subprotocol, which the client has to ask for.
+    #[allow(clippy::result_large_err)] // The signature tungstenite's callback wants
+    fn handshake(request: &Request, mut response: Response) -> Result<Response, ErrorResponse> {
+        assert_eq!(request.headers()[SEC_WEBSOCKET_PROTOCOL], "mcp");
+        let protocol = HeaderValue::from_static("mcp");
+        response
+            .headers_mut()
+            .insert(SEC_WEBSOCKET_PROTOCOL, protocol);
+        Ok(response)
+    }
+
+    /// Serves [`EchoServer`] to the first connection on `listener`, then sends a close frame
+    /// once `close` fires.
+    async fn serve_one(listener: TcpListener, mut close: oneshot::Receiver<()>) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let socket = tokio_tungstenite::accept_hdr_async(stream, handshake)
+            .await
+            .unwrap();
+        let (mut sink, stream) = socket.split();
+
+        let incoming = stream.filter_map(|frame| async move {
+            match frame {
+                Ok(Message::Text(text)) => serde_json::from_str(text.as_str()).ok(),
+                _ => None,
+            }
+        });
+        let (outgoing, mut outgoing_rx) = futures::channel::mpsc::unbounded();
+        let writer = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    message = outgoing_rx.next() => match message {
+                        Some(message) => {
+                            let text = serde_json::to_string(&message).unwrap();
+                            sink.send(Message::text(text)).await.unwrap();
+                        }
+                        None => break,
+                    },
+                    _ = &mut close => break,
+                }
+            }
+            let _ = sink.send(Message::Close(None)).await;
+        });
+
+        let server = EchoServer
+            .serve((outgoing, Box::pin(incoming)))
+            .await
+            .unwrap();
+        writer.await.unwrap();
+        drop(server);
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (close, close_rx) = oneshot::channel();
+        let server = tokio::spawn(serve_one(listener, close_rx));
+
+        let transport = WebSocketTransport::connect(&url, HeaderMap::new())
+            .await
+            .unwrap();
+        let (notified, mut notifications) = mpsc::unbounded_channel();
+        let client = Notified(notified).serve(transport).await.unwrap();
+        assert!(client.peer_info().unwrap().capabilities.tools.is_some());
+
+        let tools = client.list_tools(None).await.unwrap();
+        assert_eq!(tools.tools.len(), 1);
+        assert_eq!(tools.tools[0].name, "echo");
+        tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+            .await
+            .expect("no tools/list_changed notification")
+            .unwrap();
+
+        // The server closing the socket ends the client's session
+        close.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), client.waiting())
+            .await
+            .expect("session outlived the close frame")
+            .unwrap();
+        server.await.unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { useChatStore } from '../stores/chat';
-import { useSettingsStore } from '../stores/settings';
+import { useSettingsStore, withoutSecrets } from '../stores/settings';
 
 export class BackupService {
     private static instance: BackupService;
@@ -33,7 +33,7 @@ export class BackupService {
                     endpoints: settingsStore.endpoints,
                     models: settingsStore.models,
                     systemPrompts: settingsStore.systemPrompts,
-                    mcpServers: settingsStore.mcpServers,
+                    mcpServers: withoutSecrets(settingsStore.mcpServers),
                 }
             };
 
@@ -1,8 +1,8 @@
 import type { Endpoint, Model } from '../stores/settings';
-import type { Message, ToolCall, ToolResult, EnabledMcpTool } from '../stores/chat';
+import type { Attachment, Message, ToolCall, ToolResult, EnabledMcpTool } from '../stores/chat';
 import { useSettingsStore } from '../stores/settings';
 import { useChatStore } from '../stores/chat';
-import { getMcpClient, type McpTool } from './mcp';
+import { getMcpClient, getMcpCatalog, callQualifiedMcpTool, cancelMcpCall, recordToolAudit, getMcpServerInfo, type McpTool } from './mcp';
 import { clientTools, handleClientToolCall } from './clientTools';
 import { parsePartialJson } from '../utils/partialJson';
 import { fetch } from '@tauri-apps/plugin-http';
@@ -18,6 +18,41 @@ export interface SamplerSettings {
   temperature?: number;
 }
 
+// Tool messages can only carry text, so images returned by tools go in a user message after them
+function toolImagesMessage(model: Model, results: ToolResult[]) {
+  const images = results.flatMap(r => r.attachments || []).filter(a => a.type.startsWith('image/'));
+  if (!model.supportsVision || images.length === 0) return undefined;
+  return {
+    role: 'user',
+    content: [
+      { type: 'text', text: 'Images returned by the tool calls above:' },
+      ...images.map(img => ({ type: 'image_url', image_url: { url: img.content } }))
+    ]
+  };
+}
+
+// Text resources from a tool result become artifacts under mcp/<server id>/. HTML is kept as
+// plain text so the preview never runs a server's scripts, and an artifact the server didn't
+// make is never overwritten.
+function saveMcpResources(sessionId: string, serverId: string, resources: Attachment[]) {
+  const chatStore = useChatStore();
+  for (const resource of resources) {
+    if (!resource.name.startsWith(`mcp/${serverId}/`)) continue;
+    const existing = chatStore.getArtifactsForSession(sessionId).find(a => a.path === resource.name);
+    if (existing && existing.mcpServerId !== serverId) {
+      console.warn(`Not overwriting artifact ${resource.name} with an MCP resource`);
+      continue;
+    }
+    chatStore.createArtifact(sessionId, {
+      path: resource.name,
+      title: resource.name.split('/').pop() || resource.name,
+      type: resource.type === 'text/html' ? 'text/plain' : resource.type,
+      content: resource.content,
+      mcpServerId: serverId
+    });
+  }
+}
+
 export async function sendMessage(
   endpoint: Endpoint,
   model: Model,
@@ -44,11 +79,19 @@ export async function sendMessage(
     if (m.role === 'assistant' && m.parts && m.parts.length > 0) {
       let pendingContent = '';
       let pendingToolCalls: any[] = [];
+      let pendingResults: ToolResult[] = [];
+      const flushImages = () => {
+        const images = toolImagesMessage(model, pendingResults);
+        if (images) apiMessages.push(images);
+        pendingResults = [];
+      };
 
       for (const part of m.parts) {
         if (part.type === 'text' && part.content) {
+          flushImages();
           pendingContent += part.content;
         } else if (part.type === 'tool-call' && part.toolCall) {
+          flushImages();
           pendingToolCalls.push({
             id: part.toolCall.id,
             type: 'function',
@@ -82,6 +125,7 @@ export async function sendMessage(
             name: toolName,
             content: content
           });
+          pendingResults.push(part.toolResult);
         }
       }
 
@@ -93,6 +137,7 @@ export async function sendMessage(
           tool_calls: pendingToolCalls.length > 0 ? pendingToolCalls : undefined
         });
       }
+      flushImages();
 
       continue;
     }
@@ -164,6 +209,8 @@ export async function sendMessage(
           content: content
         });
       }
+      const images = toolImagesMessage(model, m.toolResults);
+      if (images) apiMessages.push(images);
     }
   }
 
@@ -177,62 +224,58 @@ export async function sendMessage(
     tools.push(...clientTools);
 
     // Use enabled tools from chat session if provided, otherwise use all enabled servers
-    if (enabledMcpTools && enabledMcpTools.length > 0) {
-      // Load only the tools specified for this chat
-      for (const enabledTool of enabledMcpTools) {
-        const server = settingsStore.mcpServers.find(s => s.id === enabledTool.serverId && s.enabled);
-        if (!server) continue;
+    const useChatTools = !!enabledMcpTools && enabledMcpTools.length > 0;
+    const servers = settingsStore.mcpServers.filter(s =>
+      s.enabled && (!useChatTools || enabledMcpTools!.some(et => et.serverId === s.id))
+    );
+
+    const instructions: string[] = [];
+    for (const entry of await getMcpCatalog(servers)) {
+      if (entry.error) {
+        console.error(`Failed to load tools from MCP server ${entry.serverId}:`, entry.error);
+        continue;
+      }
+      const server = servers.find(s => s.id === entry.serverId)!;
+      try {
+        const info = await getMcpServerInfo(server.id);
+        if (info.instructions?.trim()) {
+          instructions.push(`## ${server.name}\n${info.instructions.trim()}`);
+        }
+      } catch (e) {
+        console.error(`Failed to get info for MCP server ${server.name}:`, e);
+      }
+      const enabledTool = enabledMcpTools?.find(et => et.serverId === entry.serverId);
 
-        try {
-          const client = await getMcpClient(server);
-          const serverTools = await client.listTools();
-
-          for (const tool of serverTools) {
-            // If toolNames is empty, all tools are enabled
-            const isToolEnabled = enabledTool.toolNames.length === 0 ||
-              enabledTool.toolNames.includes(tool.name);
-
-            if (isToolEnabled) {
-              mcpToolsMap.set(tool.name, { serverId: server.id, tool });
-
-              tools.push({
-                type: 'function',
-                function: {
-                  name: tool.name,
-                  description: tool.description,
-                  parameters: tool.inputSchema
-                }
-              });
+      for (const tool of entry.tools) {
+        // If toolNames is empty, all tools are enabled
+        const isToolEnabled = !enabledTool ||
+          enabledTool.toolNames.length === 0 ||
+          enabledTool.toolNames.includes(tool.name);
+
+        if (isToolEnabled) {
+          // Qualified names keep same-named tools from different servers apart
+          mcpToolsMap.set(tool.qualifiedName, { serverId: entry.serverId, tool });
+
+          tools.push({
+            type: 'function',
+            function: {
+              name: tool.qualifiedName,
+              description: tool.description,
+              parameters: tool.inputSchema
             }
-          }
-        } catch (e) {
-          console.error(`Failed to load tools from MCP server ${server.name}:`, e);
+          });
         }
       }
-    } else {
-      // Fallback: Load all tools from all enabled servers (backward compatibility)
-      const enabledServers = settingsStore.mcpServers.filter(s => s.enabled);
+    }
 
-      for (const server of enabledServers) {
-        try {
-          const client = await getMcpClient(server);
-          const serverTools = await client.listTools();
-
-          for (const tool of serverTools) {
-            mcpToolsMap.set(tool.name, { serverId: server.id, tool });
-
-            tools.push({
-              type: 'function',
-              function: {
-                name: tool.name,
-                description: tool.description,
-                parameters: tool.inputSchema
-              }
-            });
-          }
-        } catch (e) {
-          console.error(`Failed to load tools from MCP server ${server.name}:`, e);
-        }
+    // Servers can explain how their tools fit together; pass that on to the model
+    if (instructions.length > 0) {
+      const text = `Instructions from connected MCP servers:\n\n${instructions.join('\n\n')}`;
+      const system = apiMessages.find(m => m.role === 'system');
+      if (system && typeof system.content === 'string') {
+        system.content = `${system.content}\n\n${text}`;
+      } else {
+        apiMessages.unshift({ role: 'system', content: text });
       }
     }
   }
@@ -440,31 +483,54 @@ export async function sendMessage(
 
         let result = '';
         let isError = false;
+        let attachments: Attachment[] = [];
+        let structuredContent: any;
+        let schemaViolations: string[] = [];
         const mcpTool = mcpToolsMap.get(toolName);
 
         if (clientToolNames.has(toolName)) {
+          const startedAt = Date.now();
           try {
             result = await handleClientToolCall(toolName, args, sessionId);
           } catch (e: any) {
             result = `Error executing client tool: ${e.message}`;
             isError = true;
           }
+          recordToolAudit({
+            timestamp: startedAt,
+            sessionId,
+            tool: toolName,
+            arguments: args,
+            durationMs: Date.now() - startedAt,
+            resultSize: new TextEncoder().encode(result).length,
+            isError,
+            error: isError ? result : undefined
+          }).catch(console.error);
         } else if (mcpTool) {
           try {
             const server = settingsStore.mcpServers.find(s => s.id === mcpTool.serverId);
             if (server) {
-              const client = await getMcpClient(server);
-              const toolResult = await client.callTool(toolName, args);
-
-              // Format result
-              if (toolResult.content) {
-                result = toolResult.content.map((c: any) => {
-                  if (c.type === 'text') return c.text;
-                  return JSON.stringify(c);
-                }).join('\n');
-              } else {
-                result = JSON.stringify(toolResult);
+              await getMcpClient(server);
+              // Stopping the chat should stop the tool too, not just the stream
+              const cancel = () => { cancelMcpCall(call.id, sessionId).catch(console.error); };
+              signal?.addEventListener('abort', cancel);
+              let toolResult;
+              try {
+                const projectId = useChatStore().sessions.find(s => s.id === sessionId)?.projectId;
+                toolResult = await callQualifiedMcpTool(toolName, args, call.id, (progress) => {
+                  call.progress = progress;
+                  onUpdate({ toolCalls: [...parsedToolCalls] });
+                }, { sessionId, projectId });
+              } finally {
+                signal?.removeEventListener('abort', cancel);
               }
+
+              result = toolResult.text;
+              isError = toolResult.isError;
+              attachments = toolResult.attachments;
+              structuredContent = toolResult.structuredContent ?? undefined;
+              schemaViolations = toolResult.schemaViolations;
+              saveMcpResources(sessionId, mcpTool.serverId, toolResult.resources);
             } else {
               result = 'Error: MCP Server not found';
               isError = true;
@@ -481,7 +547,10 @@ export async function sendMessage(
         const toolResult: ToolResult = {
           callId: call.id,
           result: result,
-          isError
+          isError,
+          attachments: attachments.length > 0 ? attachments : undefined,
+          structuredContent,
+          schemaViolations: schemaViolations.length > 0 ? schemaViolations : undefined
         };
         toolResults.push(toolResult);
 
@@ -494,6 +563,9 @@ export async function sendMessage(
         } as any);
       }
 
+      const images = toolImagesMessage(model, toolResults);
+      if (images) currentMessages.push(images);
+
       // Update the assistant message with tool results
       onUpdate({ toolResults });
     }
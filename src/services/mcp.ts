@@ -1,5 +1,7 @@
-import { invoke } from '@tauri-apps/api/core';
-import { type McpServer } from '../stores/settings';
+import { invoke, Channel } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { useSettingsStore, type McpServer } from '../stores/settings';
+import type { Attachment, Message } from '../stores/chat';
 
 export interface McpTool {
   name: string;
@@ -7,21 +9,127 @@ export interface McpTool {
   inputSchema: any;
 }
 
+export interface McpResource {
+  uri: string;
+  name: string;
+  title?: string;
+  description?: string;
+  mimeType?: string;
+  size?: number;
+}
+
+export interface McpResourceTemplate {
+  uriTemplate: string;
+  name: string;
+  title?: string;
+  description?: string;
+  mimeType?: string;
+}
+
+export interface McpResourceContents {
+  uri: string;
+  mimeType?: string;
+  text?: string;
+  blob?: string;
+}
+
+export interface McpPrompt {
+  name: string;
+  title?: string;
+  description?: string;
+  arguments?: { name: string; title?: string; description?: string; required?: boolean }[];
+}
+
+export interface McpRenderedPrompt {
+  description?: string;
+  messages: Message[];
+}
+
+export type McpCompletionReference =
+  | { type: 'ref/prompt'; name: string }
+  | { type: 'ref/resource'; uri: string };
+
+export interface McpCompletion {
+  values: string[]; // Empty when the server doesn't support completion
+  total?: number;
+  hasMore?: boolean;
+}
+
+export interface McpProgress {
+  progress: number;
+  total?: number;
+  message?: string;
+}
+
+// A tool result split by content type on the Rust side
+export interface McpToolOutput {
+  text: string; // For the model, with placeholders for what was split out
+  attachments: Attachment[]; // Images, audio and binary resources
+  resources: Attachment[]; // Embedded text resources, kept as artifacts under mcp/<server id>/
+  structuredContent?: any; // Left out when it doesn't match the tool's outputSchema
+  schemaViolations: string[];
+  isError: boolean;
+}
+
+// Who a tool call is made for, used by the tool policy
+export interface McpCallContext {
+  sessionId?: string;
+  projectId?: string;
+}
+
+export interface McpCatalogTool extends McpTool {
+  qualifiedName: string; // Unique across servers, e.g. `files__search`
+}
+
+export interface McpCatalogEntry {
+  serverId: string;
+  tools: McpCatalogTool[];
+  error?: string;
+}
+
+// What a server declared when it initialized
+export interface McpServerInfo {
+  protocolVersion: string;
+  capabilities: {
+    completions?: object;
+    logging?: object;
+    prompts?: { listChanged?: boolean };
+    resources?: { subscribe?: boolean; listChanged?: boolean };
+    tools?: { listChanged?: boolean };
+    experimental?: Record<string, object>;
+  };
+  serverInfo: { name: string; title?: string; version: string };
+  instructions?: string;
+}
+
 export class McpClient {
   constructor(public server: McpServer) {}
 
   async connect(): Promise<void> {
-    console.log(`Connecting to MCP server ${this.server.name} at ${this.server.url} via ${this.server.transport}`);
+    const target = this.server.transport === 'stdio' ? this.server.command : this.server.url;
+    console.log(`Connecting to MCP server ${this.server.name} at ${target} via ${this.server.transport}`);
+    await setMcpRoots(this.server.id, rootsFor(this.server));
     await invoke('mcp_connect', {
       id: this.server.id,
-      url: this.server.url,
-      transport: this.server.transport
+      config: {
+        name: this.server.name,
+        transport: this.server.transport,
+        url: this.server.url,
+        command: this.server.command,
+        args: this.server.args || [],
+        cwd: this.server.cwd,
+        env: this.server.env || {},
+        secretEnv: this.server.secretEnv || {},
+        inheritEnv: this.server.inheritEnv || [],
+        headers: this.server.headers || {},
+        secretHeaders: this.server.secretHeaders || {},
+        bearerTokenSecret: this.server.bearerTokenSecret
+      }
     });
   }
 
-  disconnect() {
-    // No explicit disconnect needed for now as Rust manages state, 
-    // but we could add a command if needed.
+  async disconnect(): Promise<void> {
+    await invoke('mcp_disconnect', { id: this.server.id });
   }
 
   async listTools(): Promise<McpTool[]> {
@@ -31,18 +139,89 @@ export class McpClient {
     return res.tools || [];
   }
 
-  async callTool(name: string, args: any): Promise<any> {
-    const res = await invoke('mcp_call_tool', {
+  async callTool(name: string, args: any, callId?: string, onProgress?: (progress: McpProgress) => void, context?: McpCallContext): Promise<McpToolOutput> {
+    return await invoke('mcp_call_tool', {
       id: this.server.id,
       name,
-      args
+      args,
+      callId,
+      onProgress: progressChannel(onProgress),
+      context
+    });
+  }
+
+  async listResources(): Promise<McpResource[]> {
+    return await invoke('mcp_list_resources', { id: this.server.id });
+  }
+
+  async listResourceTemplates(): Promise<McpResourceTemplate[]> {
+    return await invoke('mcp_list_resource_templates', { id: this.server.id });
+  }
+
+  async readResource(uri: string): Promise<McpResourceContents[]> {
+    const res: any = await invoke('mcp_read_resource', { id: this.server.id, uri });
+    return res.contents || [];
+  }
+
+  async listPrompts(): Promise<McpPrompt[]> {
+    return await invoke('mcp_list_prompts', { id: this.server.id });
+  }
+
+  async getPrompt(name: string, args: Record<string, string> = {}): Promise<McpRenderedPrompt> {
+    return await invoke('mcp_get_prompt', { id: this.server.id, name, arguments: args });
+  }
+
+  // Values the server suggests for an argument, given what has been typed so far
+  // and the arguments already filled in
+  async completePromptArgument(prompt: string, argument: string, value: string, args?: Record<string, string>): Promise<McpCompletion> {
+    return await this.complete({ type: 'ref/prompt', name: prompt }, argument, value, args);
+  }
+
+  async completeResourceArgument(uriTemplate: string, argument: string, value: string, args?: Record<string, string>): Promise<McpCompletion> {
+    return await this.complete({ type: 'ref/resource', uri: uriTemplate }, argument, value, args);
+  }
+
+  private async complete(reference: McpCompletionReference, name: string, value: string, args?: Record<string, string>): Promise<McpCompletion> {
+    return await invoke('mcp_complete', {
+      id: this.server.id,
+      reference,
+      argument: { name, value },
+      arguments: args
     });
-    return res;
   }
 }
 
 const activeClients = new Map<string, McpClient>();
 
+// Folder of the active chat's project, added to the roots of servers that follow it
+let projectFolder: string | undefined;
+
+function rootsFor(server: McpServer): string[] {
+  const roots = server.roots || [];
+  return server.useProjectFolder && projectFolder && !roots.includes(projectFolder) ? [...roots, projectFolder] : roots;
+}
+
+// Reports a server's new roots to its live session, which notifies it with roots/list_changed
+export async function updateMcpRoots(server: McpServer): Promise<void> {
+  const client = activeClients.get(server.id);
+  if (client) client.server = server;
+  await setMcpRoots(server.id, rootsFor(server));
+}
+
+// Re-scopes the servers that follow the project folder when the active project changes
+export async function setMcpProjectFolder(folder?: string): Promise<void> {
+  if (folder === projectFolder) return;
+  projectFolder = folder;
+  for (const client of activeClients.values()) {
+    if (!client.server.useProjectFolder) continue;
+    try {
+      await updateMcpRoots(client.server);
+    } catch (e) {
+      console.error(`Failed to update roots for ${client.server.name}:`, e);
+    }
+  }
+}
+
 export async function getMcpClient(server: McpServer): Promise<McpClient> {
   if (activeClients.has(server.id)) {
     const client = activeClients.get(server.id)!;
@@ -56,6 +235,271 @@ export async function getMcpClient(server: McpServer): Promise<McpClient> {
   return client;
 }
 
-export function disconnectAllMcpClients() {
-  activeClients.clear();
-}
\ No newline at end of file
+export type McpConnectionState = 'connected' | 'reconnecting' | 'failed' | 'disconnected';
+
+export type McpTransport = 'auto' | 'sse' | 'http' | 'stdio' | 'websocket';
+
+export interface McpStatus {
+  id: string;
+  status: McpConnectionState;
+  transport: McpTransport; // Negotiated transport, never 'auto'
+  connected: boolean;
+}
+
+export interface McpStatusEvent {
+  id: string;
+  status: McpConnectionState;
+  attempt: number;
+  error?: string;
+}
+
+export async function disconnectMcpClient(id: string): Promise<void> {
+  const client = activeClients.get(id);
+  activeClients.delete(id);
+  if (client) {
+    await client.disconnect();
+  }
+}
+
+// For a server being deleted: closes its session and removes its stored OAuth sign-in
+export async function forgetMcpServer(id: string): Promise<void> {
+  activeClients.delete(id);
+  await invoke('mcp_forget_server', { id });
+}
+
+export interface McpImportReport {
+  name: string;
+  status: 'imported' | 'skipped' | 'failed';
+  id?: string;
+  config?: Omit<McpServer, 'id' | 'enabled' | 'roots'>;
+  transport?: McpTransport;
+  message?: string;
+}
+
+// Imports an `mcpServers` config (pasted JSON or a file path). Rust validates and
+// connects each entry; the ones that connected are added to the settings.
+export async function importMcpServers(source: { json?: string; path?: string }): Promise<McpImportReport[]> {
+  const settingsStore = useSettingsStore();
+  const reports = await invoke<McpImportReport[]>('mcp_import_servers', {
+    ...source,
+    existing: settingsStore.mcpServers.map(s => s.name)
+  });
+  for (const report of reports) {
+    if (report.status !== 'imported' || !report.id || !report.config) continue;
+    const server: McpServer = {
+      ...report.config,
+      id: report.id,
+      name: report.config.name || report.name,
+      url: report.config.url || '',
+      enabled: true
+    };
+    settingsStore.addMcpServer(server);
+    // Already connected on the Rust side
+    activeClients.set(server.id, new McpClient(server));
+  }
+  return reports;
+}
+
+// Replaces the server's session now, or opens one if it was never connected
+export async function reconnectMcpClient(server: McpServer): Promise<void> {
+  if (activeClients.has(server.id)) {
+    await invoke('mcp_reconnect', { id: server.id });
+  } else {
+    await getMcpClient(server);
+  }
+}
+
+// Tools for the given servers from the Rust-side cache, connecting any that aren't yet
+export async function getMcpCatalog(servers: McpServer[]): Promise<McpCatalogEntry[]> {
+  const connected: McpServer[] = [];
+  for (const server of servers) {
+    try {
+      await getMcpClient(server);
+      connected.push(server);
+    } catch (e) {
+      console.error(`Failed to connect to MCP server ${server.name}:`, e);
+    }
+  }
+  if (connected.length === 0) return [];
+  return await invoke('mcp_catalog', { ids: connected.map(s => s.id) });
+}
+
+function progressChannel(onProgress?: (progress: McpProgress) => void): Channel<McpProgress> | undefined {
+  if (!onProgress) return undefined;
+  const channel = new Channel<McpProgress>();
+  channel.onmessage = onProgress;
+  return channel;
+}
+
+// Calls a tool by the qualified name it was given in the catalog
+export async function callQualifiedMcpTool(qualifiedName: string, args: any, callId?: string, onProgress?: (progress: McpProgress) => void, context?: McpCallContext): Promise<McpToolOutput> {
+  return await invoke('mcp_call_qualified', {
+    qualifiedName,
+    args,
+    callId,
+    onProgress: progressChannel(onProgress),
+    context
+  });
+}
+
+export function onMcpToolsChanged(handler: (event: { id: string }) => void): Promise<UnlistenFn> {
+  return listen<{ id: string }>('mcp://tools-changed', (event) => handler(event.payload));
+}
+
+// Calls are identified by chat session and tool-call id, as providers reuse ids across chats
+export async function cancelMcpCall(callId: string, sessionId?: string): Promise<boolean> {
+  return await invoke('mcp_cancel_call', { sessionId, callId });
+}
+
+// Secrets for stdio servers' `secretEnv`, kept in the system keychain rather than settings
+export async function setMcpSecret(name: string, value: string): Promise<void> {
+  await invoke('mcp_secret_set', { name, value });
+}
+
+export async function deleteMcpSecret(name: string): Promise<void> {
+  await invoke('mcp_secret_delete', { name });
+}
+
+// Which of the named secrets are stored; values never leave Rust
+export async function getMcpSecretStatus(names: string[]): Promise<Record<string, boolean>> {
+  return await invoke('mcp_secret_status', { names });
+}
+
+export async function setMcpRoots(id: string, paths: string[]): Promise<void> {
+  await invoke('mcp_set_roots', {
+    id,
+    directories: paths.map(path => ({ path }))
+  });
+}
+
+export async function getMcpServerInfo(id: string): Promise<McpServerInfo> {
+  return await invoke('mcp_server_info', { id });
+}
+
+export async function getMcpStatus(): Promise<McpStatus[]> {
+  return await invoke('mcp_status');
+}
+
+export function onMcpStatus(handler: (event: McpStatusEvent) => void): Promise<UnlistenFn> {
+  return listen<McpStatusEvent>('mcp://status', e => handler(e.payload));
+}
+
+export interface McpSamplingRequest {
+  requestId: string;
+  serverId: string;
+  model: string;
+  systemPrompt?: string;
+  messages: { role: 'user' | 'assistant'; content: { type: string; text?: string } }[];
+  maxTokens: number;
+}
+
+// Points server-initiated sampling at the model chosen in settings (or disables it)
+export async function configureMcpSampling(): Promise<void> {
+  const settingsStore = useSettingsStore();
+  const model = settingsStore.models.find(m => m.id === settingsStore.mcpSamplingModelId);
+  const endpoint = model && settingsStore.endpoints.find(e => e.id === model.endpointId);
+  const config = model && endpoint ? { url: endpoint.url, apiKey: endpoint.apiKey, model: model.id } : null;
+  await invoke('mcp_set_sampling_config', { config });
+}
+
+export function onMcpSamplingRequest(handler: (request: McpSamplingRequest) => void): Promise<UnlistenFn> {
+  return listen<McpSamplingRequest>('mcp://sampling-request', e => handler(e.payload));
+}
+
+export async function respondToMcpSampling(requestId: string, approved: boolean): Promise<void> {
+  await invoke('mcp_sampling_respond', { requestId, approved });
+}
+
+export type McpPolicyAction = 'allow' | 'deny' | 'ask';
+
+export interface McpPolicyRule {
+  server?: string; // Server id, any server when unset
+  tool?: string; // Glob over the tool name
+  destructive?: boolean;
+  readOnly?: boolean;
+  action: McpPolicyAction;
+}
+
+// Rules for a project, or the global rules when no project is given
+export async function getToolPolicy(projectId?: string): Promise<McpPolicyRule[]> {
+  return await invoke('tool_policy_get', { projectId });
+}
+
+export async function setToolPolicy(projectId: string | undefined, rules: McpPolicyRule[]): Promise<void> {
+  await invoke('tool_policy_set', { projectId, rules });
+}
+
+export interface ToolAuditEntry {
+  timestamp: number; // ms since epoch
+  sessionId?: string;
+  server?: string; // Unset for client-side tools
+  tool: string;
+  arguments: any;
+  durationMs: number;
+  resultSize: number;
+  isError: boolean;
+  error?: string;
+}
+
+export interface ToolAuditFilter {
+  sessionId?: string;
+  server?: string;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+// MCP calls are logged by Rust; client-side tools are logged through this
+export async function recordToolAudit(entry: ToolAuditEntry): Promise<void> {
+  await invoke('tool_audit_record', { entry });
+}
+
+export async function queryToolAudit(filter: ToolAuditFilter = {}): Promise<ToolAuditEntry[]> {
+  return await invoke('tool_audit_query', { filter });
+}
+
+export interface McpToolApprovalRequest {
+  requestId: string;
+  serverId: string;
+  tool: string;
+  arguments: any;
+  sessionId?: string;
+}
+
+export function onMcpToolApproval(handler: (request: McpToolApprovalRequest) => void): Promise<UnlistenFn> {
+  return listen<McpToolApprovalRequest>('mcp://tool-approval', e => handler(e.payload));
+}
+
+export async function respondToMcpToolApproval(requestId: string, approved: boolean): Promise<void> {
+  await invoke('tool_approval_respond', { requestId, approved });
+}
+
+export interface McpElicitationProperty {
+  type: 'string' | 'number' | 'integer' | 'boolean';
+  title?: string;
+  description?: string;
+  enum?: string[];
+  enumNames?: string[];
+  default?: any;
+}
+
+export interface McpElicitationRequest {
+  requestId: string;
+  serverId: string;
+  message: string;
+  requestedSchema: {
+    properties: Record<string, McpElicitationProperty>;
+    required?: string[];
+  };
+}
+
+export type McpElicitationAction = 'accept' | 'decline' | 'cancel';
+
+export function onMcpElicitationRequest(handler: (request: McpElicitationRequest) => void): Promise<UnlistenFn> {
+  return listen<McpElicitationRequest>('mcp://elicitation-request', e => handler(e.payload));
+}
+
+// Rejects with the validation errors if accepted content doesn't match the schema
+export async function respondToMcpElicitation(requestId: string, action: McpElicitationAction, content?: Record<string, any>): Promise<void> {
+  await invoke('mcp_elicitation_respond', { requestId, reply: { action, content } });
+}
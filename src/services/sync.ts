@@ -1,4 +1,4 @@
-import { useSettingsStore, type SettingsState } from '../stores/settings';
+import { useSettingsStore, withoutSecrets, type SettingsState } from '../stores/settings';
 import { useSyncStore } from '../stores/sync';
 import { useChatStore, type ChatSession, type Project } from '../stores/chat';
 
@@ -191,7 +191,7 @@ export class SyncService {
                     endpoints: this.settingsStore.endpoints,
                     models: this.settingsStore.models,
                     systemPrompts: this.settingsStore.systemPrompts,
-                    mcpServers: this.settingsStore.mcpServers,
+                    mcpServers: withoutSecrets(this.settingsStore.mcpServers),
                     updatedAt: localUpdatedAt
                 };
                 await this.setValue(key, state);
@@ -202,7 +202,7 @@ export class SyncService {
                 endpoints: this.settingsStore.endpoints,
                 models: this.settingsStore.models,
                 systemPrompts: this.settingsStore.systemPrompts,
-                mcpServers: this.settingsStore.mcpServers,
+                mcpServers: withoutSecrets(this.settingsStore.mcpServers),
                 updatedAt: localUpdatedAt
             };
             await this.setValue(key, state);
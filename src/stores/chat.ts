@@ -17,6 +17,7 @@ export interface Artifact {
   type: string;
   title: string;
   content: string;
+  mcpServerId?: string; // Set when saved from an MCP server's resource
   createdAt: number;
   updatedAt: number;
 }
@@ -25,12 +26,16 @@ export interface ToolCall {
   id: string;
   name: string;
   arguments: any;
+  progress?: { progress: number; total?: number; message?: string };
 }
 
 export interface ToolResult {
   callId: string;
   result: any;
   isError?: boolean;
+  attachments?: Attachment[]; // Images, audio and binary resources returned by MCP tools
+  structuredContent?: any; // Matched the tool's output schema, if it declares one
+  schemaViolations?: string[]; // Why structured content was rejected
 }
 
 export type MessagePartType = 'text' | 'reasoning' | 'tool-call' | 'tool-result';
@@ -65,6 +70,7 @@ export interface Message {
 export interface Project {
   id: string;
   name: string;
+  folder?: string; // Directory exposed to MCP servers that follow the project folder
   order: number;
   isExpanded: boolean;
   createdAt: number;
@@ -443,6 +449,13 @@ export const useChatStore = defineStore('chat', () => {
     return null;
   }
 
+  // Appends messages rendered from an MCP prompt as a chain below the current leaf
+  function insertPromptMessages(sessionId: string, messages: Message[]) {
+    for (const message of messages) {
+      addMessage(sessionId, { ...message });
+    }
+  }
+
   function editMessage(sessionId: string, messageId: string, newContent: string) {
     const session = sessions.value.find(s => s.id === sessionId);
     if (session) {
@@ -988,6 +1001,7 @@ Title:`;
     updateProject,
     deleteSession,
     addMessage,
+    insertPromptMessages,
     editMessage,
     deleteMessage,
     updateSessionSettings,
@@ -1058,6 +1072,7 @@ function createArtifact(sessionId: string, artifact: Partial<Artifact> & { type:
           type: artifact.type,
           title: artifact.title,
           content: artifact.content,
+          mcpServerId: artifact.mcpServerId,
           createdAt: artifact.createdAt || Date.now(),
           updatedAt: Date.now()
         };
@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { Store } from '@tauri-apps/plugin-store';
+import { invoke } from '@tauri-apps/api/core';
 
 export interface Endpoint {
   id: string;
@@ -29,15 +30,34 @@ export interface McpServer {
   id: string;
   name: string;
   url: string;
-  transport: 'sse' | 'http';
+  transport: 'auto' | 'sse' | 'http' | 'stdio' | 'websocket';
+  command?: string;
+  args?: string[];
+  cwd?: string;
+  env?: Record<string, string>;
+  secretEnv?: Record<string, string>; // Variable name -> name of a secret in the system keychain
+  inheritEnv?: string[]; // Extra variables passed through from the app's environment
+  headers?: Record<string, string>; // Sent with every request on the HTTP transports and the WebSocket handshake
+  secretHeaders?: Record<string, string>; // Header name -> name of a secret in the system keychain
+  bearerTokenSecret?: string; // Name of the keychain secret holding the bearer token
+  bearerToken?: string; // Plain-text token from older versions, moved to the keychain on load
+  roots?: string[]; // Directories the server may access, reported via roots/list
+  useProjectFolder?: boolean; // Also expose the active chat's project folder as a root
   enabled: boolean;
 }
 
+// Server configs as they may leave this device, for sync and backups. Secrets live in the
+// keychain, so only a plain-text token left over from an older version needs dropping.
+export function withoutSecrets(servers: McpServer[]): McpServer[] {
+  return servers.map(({ bearerToken: _, ...server }) => server);
+}
+
 export interface SettingsState {
   endpoints: Endpoint[];
   models: Model[];
   systemPrompts: SystemPrompt[];
   mcpServers: McpServer[];
+  mcpSamplingModelId?: string;
   syncToken?: string;
   lastSyncTime?: number;
   updatedAt: number;
@@ -49,6 +69,7 @@ export const useSettingsStore = defineStore('settings', () => {
   const models = ref<Model[]>([]);
   const systemPrompts = ref<SystemPrompt[]>([]);
   const mcpServers = ref<McpServer[]>([]);
+  const mcpSamplingModelId = ref<string>('');
   const syncToken = ref<string>('');
   const lastSyncTime = ref<number>(0);
   const updatedAt = ref<number>(0);
@@ -74,6 +95,10 @@ export const useSettingsStore = defineStore('settings', () => {
 
     const savedMcpServers = await s.get<McpServer[]>('mcpServers');
     if (savedMcpServers) mcpServers.value = savedMcpServers;
+    await migrateBearerTokens();
+
+    const savedSamplingModelId = await s.get<string>('mcpSamplingModelId');
+    if (savedSamplingModelId) mcpSamplingModelId.value = savedSamplingModelId;
 
     const savedSyncToken = await s.get<string>('syncToken');
     if (savedSyncToken) syncToken.value = savedSyncToken;
@@ -85,12 +110,29 @@ export const useSettingsStore = defineStore('settings', () => {
     if (savedUpdatedAt) updatedAt.value = savedUpdatedAt;
   }
 
+  // Bearer tokens used to be kept in settings.json; move any left there to the keychain
+  async function migrateBearerTokens() {
+    const legacy = mcpServers.value.filter(s => s.bearerToken);
+    for (const server of legacy) {
+      const name = `mcp/${server.id}/bearer-token`;
+      try {
+        await invoke('mcp_secret_set', { name, value: server.bearerToken });
+        server.bearerTokenSecret = name;
+        delete server.bearerToken;
+      } catch (e) {
+        console.error(`Failed to move the bearer token for ${server.name} to the keychain:`, e);
+      }
+    }
+    if (legacy.length > 0) await save();
+  }
+
   async function save() {
     const s = await getStore();
     await s.set('endpoints', endpoints.value);
     await s.set('models', models.value);
     await s.set('systemPrompts', systemPrompts.value);
     await s.set('mcpServers', mcpServers.value);
+    await s.set('mcpSamplingModelId', mcpSamplingModelId.value);
     await s.set('syncToken', syncToken.value);
     await s.set('lastSyncTime', lastSyncTime.value);
     await s.set('updatedAt', updatedAt.value);
@@ -182,6 +224,12 @@ export const useSettingsStore = defineStore('settings', () => {
     }
   }
 
+  function setMcpSamplingModel(modelId: string) {
+    mcpSamplingModelId.value = modelId;
+    updatedAt.value = Date.now();
+    save();
+  }
+
   function reorderEndpoints(newOrder: Endpoint[]) {
     endpoints.value = newOrder;
     updatedAt.value = Date.now();
@@ -214,6 +262,7 @@ export const useSettingsStore = defineStore('settings', () => {
     models,
     systemPrompts,
     mcpServers,
+    mcpSamplingModelId,
     syncToken,
     lastSyncTime,
     updatedAt,
@@ -231,6 +280,7 @@ export const useSettingsStore = defineStore('settings', () => {
     addMcpServer,
     removeMcpServer,
     updateMcpServer,
+    setMcpSamplingModel,
     reorderEndpoints,
     reorderModels,
     setSyncToken,